@@ -0,0 +1,138 @@
+use std::{collections::HashMap, fmt};
+
+use crate::{parse::ParseError, solution::Part};
+
+/// The answers for the baked in inputs.
+pub const ANSWERS: &str = include_str!("../answers.txt");
+
+/// Hashes a puzzle input so answers can be looked up per input.
+///
+/// Uses 64-bit FNV-1a over the trimmed input which, unlike the std hasher,
+/// stays stable between compiler versions.
+pub fn input_hash(input: &str) -> u64 {
+	input.trim().bytes().fold(0xcbf29ce484222325, |hash, b| {
+		(hash ^ b as u64).wrapping_mul(0x100000001b3)
+	})
+}
+
+/// Known answers keyed by day, part and input hash.
+///
+/// The file format is one answer per line `<day> <part> <hash> <answer>`
+/// where the hash is written in hex, empty lines and lines starting with
+/// `#` are ignored.
+#[derive(Debug, Default)]
+pub struct Answers {
+	inner: HashMap<(u32, Part, u64), String>,
+}
+
+impl Answers {
+	pub fn parse(input: &str) -> Result<Self, ParseError> {
+		let mut inner = HashMap::new();
+
+		for line in input.lines() {
+			let line = line.trim();
+			if line.is_empty() || line.starts_with('#') {
+				continue;
+			}
+
+			let mut fields = line.split_ascii_whitespace();
+			let mut field = |expected: &str| {
+				fields.next().ok_or_else(|| {
+					ParseError::at(input, &line[line.len()..], expected)
+				})
+			};
+
+			let day = field("a day")?;
+			let day = day
+				.parse()
+				.map_err(|_| ParseError::at(input, day, "a day"))?;
+
+			let part = field("a part")?;
+			let part = part
+				.parse()
+				.ok()
+				.and_then(Part::new)
+				.ok_or_else(|| ParseError::at(input, part, "`1` or `2`"))?;
+
+			let hash = field("an input hash")?;
+			let hash = u64::from_str_radix(hash, 16)
+				.map_err(|_| ParseError::at(input, hash, "a hex input hash"))?;
+
+			let answer = field("an answer")?;
+
+			inner.insert((day, part, hash), answer.to_string());
+		}
+
+		Ok(Self { inner })
+	}
+
+	pub fn get(&self, day: u32, part: Part, hash: u64) -> Option<&str> {
+		self.inner.get(&(day, part, hash)).map(String::as_str)
+	}
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Status {
+	Pass,
+	Fail,
+	Unknown,
+}
+
+impl Status {
+	pub fn new(answer: &str, expected: Option<&str>) -> Self {
+		match expected {
+			Some(e) if e == answer => Status::Pass,
+			Some(_) => Status::Fail,
+			None => Status::Unknown,
+		}
+	}
+}
+
+impl fmt::Display for Status {
+	fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+		f.pad(match self {
+			Status::Pass => "pass",
+			Status::Fail => "FAIL",
+			Status::Unknown => "unknown",
+		})
+	}
+}
+
+#[test]
+fn test_parse() {
+	let answers = Answers::parse(
+		"\
+# day part hash answer
+1 1 00ff 1055
+
+7 2 abc 58097428661390
+",
+	)
+	.unwrap();
+
+	assert_eq!(answers.get(1, Part::One, 0xff), Some("1055"));
+	assert_eq!(answers.get(7, Part::Two, 0xabc), Some("58097428661390"));
+	assert_eq!(answers.get(7, Part::One, 0xabc), None);
+
+	assert_eq!(
+		Answers::parse("1 3 ff 10").unwrap_err(),
+		ParseError::new(1, 3, "3", "`1` or `2`")
+	);
+	assert_eq!(
+		Answers::parse("1 1 ff").unwrap_err(),
+		ParseError::new(1, 7, "", "an answer")
+	);
+}
+
+#[test]
+fn test_baked_answers() {
+	Answers::parse(ANSWERS).unwrap();
+}
+
+#[test]
+fn test_input_hash() {
+	assert_eq!(input_hash(""), 0xcbf29ce484222325);
+	assert_eq!(input_hash("a"), 0xaf63dc4c8601ec8c);
+	// surrounding whitespace does not change the input
+	assert_eq!(input_hash("L68\nL30\n"), input_hash("L68\nL30"));
+}
@@ -0,0 +1,255 @@
+use std::{
+	fmt::Write,
+	hint::black_box,
+	time::{Duration, Instant},
+};
+
+use crate::{parse::ParseError, solution::Part};
+
+/// All measured durations of one phase, sorted ascending.
+#[derive(Debug, Clone)]
+pub struct Samples(Vec<Duration>);
+
+impl Samples {
+	/// Runs `f` `runs` times measuring each run.
+	///
+	/// ## Panics
+	/// If `runs` is zero.
+	fn measure<T>(runs: usize, mut f: impl FnMut() -> T) -> Self {
+		assert!(runs > 0, "need at least one run");
+
+		let mut samples = (0..runs)
+			.map(|_| {
+				let start = Instant::now();
+				black_box(f());
+				start.elapsed()
+			})
+			.collect::<Vec<_>>();
+		samples.sort_unstable();
+
+		Self(samples)
+	}
+
+	/// Runs `solve` `runs` times, every run gets its own clone of `parsed`
+	/// which is created outside of the measurement.
+	fn solve<P: Clone, T>(
+		runs: usize,
+		parsed: &P,
+		solve: impl Fn(P) -> T,
+	) -> Self {
+		let mut inputs = vec![parsed.clone(); runs];
+		Self::measure(runs, || solve(inputs.pop().unwrap()))
+	}
+
+	pub fn min(&self) -> Duration {
+		self.0[0]
+	}
+
+	pub fn median(&self) -> Duration {
+		self.percentile(50)
+	}
+
+	pub fn p95(&self) -> Duration {
+		self.percentile(95)
+	}
+
+	/// Nearest rank percentile.
+	fn percentile(&self, p: usize) -> Duration {
+		let rank = (p * self.0.len()).div_ceil(100);
+		self.0[rank.saturating_sub(1)]
+	}
+}
+
+/// The timings of parsing and solving one day.
+#[derive(Debug, Clone)]
+pub struct DayTimings {
+	/// one `parse` phase shared by both parts, or one per part for days
+	/// with a parser for each part
+	pub parse: Vec<(&'static str, Samples)>,
+	/// indexed by [`Part`], `None` if the part was not benchmarked
+	pub parts: [Option<Samples>; 2],
+}
+
+impl DayTimings {
+	pub fn phases(&self) -> impl Iterator<Item = (&'static str, &Samples)> {
+		let parts = Part::ALL.into_iter().zip(&self.parts).filter_map(
+			|(part, samples)| {
+				let name = match part {
+					Part::One => "part1",
+					Part::Two => "part2",
+				};
+				samples.as_ref().map(|s| (name, s))
+			},
+		);
+
+		self.parse.iter().map(|(name, s)| (*name, s)).chain(parts)
+	}
+}
+
+/// Measures the parse phase and the given parts separately, each `runs`
+/// times.
+///
+/// Every part gets its own clone of the parsed input which is created outside
+/// of the measurement.
+pub fn phases<'a, P: Clone, A, B>(
+	input: &'a str,
+	runs: usize,
+	parts: &[Part],
+	parse: impl Fn(&'a str) -> Result<P, ParseError>,
+	part1: impl Fn(P) -> A,
+	part2: impl Fn(P) -> B,
+) -> Result<DayTimings, ParseError> {
+	// make sure the input is valid before measuring anything
+	let parsed = parse(input)?;
+	let parse = Samples::measure(runs, || parse(black_box(input)));
+
+	Ok(DayTimings {
+		parse: vec![("parse", parse)],
+		parts: [
+			parts
+				.contains(&Part::One)
+				.then(|| Samples::solve(runs, &parsed, &part1)),
+			parts
+				.contains(&Part::Two)
+				.then(|| Samples::solve(runs, &parsed, &part2)),
+		],
+	})
+}
+
+/// Like [`phases`] for days with a different parser for each part, every
+/// parser is measured as its own phase along with its part.
+pub fn phases_per_part<'a, P1: Clone, P2: Clone, A, B>(
+	input: &'a str,
+	runs: usize,
+	parts: &[Part],
+	parse1: impl Fn(&'a str) -> Result<P1, ParseError>,
+	part1: impl Fn(P1) -> A,
+	parse2: impl Fn(&'a str) -> Result<P2, ParseError>,
+	part2: impl Fn(P2) -> B,
+) -> Result<DayTimings, ParseError> {
+	// make sure the input is valid before measuring anything
+	let parsed1 = parts.contains(&Part::One).then(|| parse1(input));
+	let parsed2 = parts.contains(&Part::Two).then(|| parse2(input));
+	let (parsed1, parsed2) = (parsed1.transpose()?, parsed2.transpose()?);
+
+	let mut parse = Vec::new();
+	if parsed1.is_some() {
+		let samples = Samples::measure(runs, || parse1(black_box(input)));
+		parse.push(("parse1", samples));
+	}
+	if parsed2.is_some() {
+		let samples = Samples::measure(runs, || parse2(black_box(input)));
+		parse.push(("parse2", samples));
+	}
+
+	Ok(DayTimings {
+		parse,
+		parts: [
+			parsed1.map(|p| Samples::solve(runs, &p, &part1)),
+			parsed2.map(|p| Samples::solve(runs, &p, &part2)),
+		],
+	})
+}
+
+/// Formats the timings as a table.
+pub fn table(timings: &[(u32, DayTimings)]) -> String {
+	let mut s = format!(
+		"day  phase   {:>12}  {:>12}  {:>12}\n",
+		"min", "median", "p95"
+	);
+
+	for (day, timings) in timings {
+		for (phase, samples) in timings.phases() {
+			writeln!(
+				s,
+				"{day:>3}  {phase:<6}  {:>12}  {:>12}  {:>12}",
+				format!("{:.1?}", samples.min()),
+				format!("{:.1?}", samples.median()),
+				format!("{:.1?}", samples.p95()),
+			)
+			.unwrap();
+		}
+	}
+
+	s
+}
+
+/// Formats the timings as JSON, all durations are in nanoseconds.
+pub fn json(runs: usize, timings: &[(u32, DayTimings)]) -> String {
+	let days = timings
+		.iter()
+		.map(|(day, timings)| {
+			let phases = timings
+				.phases()
+				.map(|(phase, s)| {
+					format!(
+						"\"{phase}\":{{\"min\":{},\"median\":{},\"p95\":{}}}",
+						s.min().as_nanos(),
+						s.median().as_nanos(),
+						s.p95().as_nanos()
+					)
+				})
+				.collect::<Vec<_>>()
+				.join(",");
+
+			format!("{{\"day\":{day},\"phases\":{{{phases}}}}}")
+		})
+		.collect::<Vec<_>>()
+		.join(",");
+
+	format!("{{\"runs\":{runs},\"unit\":\"ns\",\"days\":[{days}]}}")
+}
+
+#[test]
+fn test_percentiles() {
+	let samples =
+		Samples((1..=20).map(Duration::from_millis).collect::<Vec<_>>());
+
+	assert_eq!(samples.min(), Duration::from_millis(1));
+	assert_eq!(samples.median(), Duration::from_millis(10));
+	assert_eq!(samples.p95(), Duration::from_millis(19));
+
+	let samples = Samples(vec![Duration::from_millis(3)]);
+	assert_eq!(samples.median(), Duration::from_millis(3));
+	assert_eq!(samples.p95(), Duration::from_millis(3));
+}
+
+#[test]
+fn test_json() {
+	let ms = |n| Samples(vec![Duration::from_millis(n)]);
+	let timings = DayTimings {
+		parse: vec![("parse", ms(1))],
+		parts: [None, Some(ms(2))],
+	};
+
+	assert_eq!(
+		json(1, &[(4, timings)]),
+		"{\"runs\":1,\"unit\":\"ns\",\"days\":[{\"day\":4,\"phases\":{\
+		 \"parse\":{\"min\":1000000,\"median\":1000000,\"p95\":1000000},\
+		 \"part2\":{\"min\":2000000,\"median\":2000000,\"p95\":2000000}}}]}"
+	);
+}
+
+#[test]
+fn test_phases_per_part() {
+	let parse = |s: &str| {
+		s.parse::<u64>()
+			.map_err(|_| ParseError::new(1, 1, s, "a number"))
+	};
+	let names = |timings: &DayTimings| {
+		timings.phases().map(|(name, _)| name).collect::<Vec<_>>()
+	};
+
+	let timings =
+		phases_per_part("7", 3, &Part::ALL, parse, |n| n, parse, |n| n)
+			.unwrap();
+	assert_eq!(names(&timings), ["parse1", "parse2", "part1", "part2"]);
+
+	let timings =
+		phases_per_part("7", 3, &[Part::Two], parse, |n| n, parse, |n| n)
+			.unwrap();
+	assert_eq!(names(&timings), ["parse2", "part2"]);
+
+	let timings = phases("7", 3, &Part::ALL, parse, |n| n, |n| n).unwrap();
+	assert_eq!(names(&timings), ["parse", "part1", "part2"]);
+}
@@ -0,0 +1,381 @@
+use std::{
+	fmt,
+	ops::{Add, AddAssign, Div, Mul, Rem, Sub, SubAssign},
+};
+
+/// An unsigned integer of arbitrary size.
+///
+/// Stored as little endian 64 bit limbs without trailing zero limbs, so zero
+/// has no limbs at all.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Hash)]
+pub struct BigUint {
+	limbs: Vec<u64>,
+}
+
+impl BigUint {
+	pub fn zero() -> Self {
+		Self::default()
+	}
+
+	pub fn is_zero(&self) -> bool {
+		self.limbs.is_empty()
+	}
+
+	/// Returns the value if it fits into a `u64`.
+	pub fn to_u64(&self) -> Option<u64> {
+		match self.limbs[..] {
+			[] => Some(0),
+			[n] => Some(n),
+			_ => None,
+		}
+	}
+
+	/// Returns the value if it fits into a `u128`.
+	pub fn to_u128(&self) -> Option<u128> {
+		match self.limbs[..] {
+			[] => Some(0),
+			[n] => Some(n as u128),
+			[low, high] => Some((high as u128) << 64 | low as u128),
+			_ => None,
+		}
+	}
+
+	/// Parses a decimal number with an optional leading `+`, like the
+	/// primitive integers do.
+	pub fn parse(s: &str) -> Option<Self> {
+		let digits = s.strip_prefix('+').unwrap_or(s);
+		if digits.is_empty() || !digits.bytes().all(|b| b.is_ascii_digit()) {
+			return None;
+		}
+
+		let mut n = Self::zero();
+		for chunk in digits.as_bytes().chunks(19) {
+			// only ascii digits, so every chunk is valid
+			let value = std::str::from_utf8(chunk).unwrap().parse().unwrap();
+			n.mul_add_limb(10u64.pow(chunk.len() as u32), value);
+		}
+
+		Some(n)
+	}
+
+	/// Returns the quotient and the remainder.
+	///
+	/// ## Panics
+	/// If `divisor` is zero.
+	pub fn div_rem(&self, divisor: &Self) -> (Self, Self) {
+		assert!(!divisor.is_zero(), "division by zero");
+
+		if let [d] = divisor.limbs[..] {
+			let mut quotient = self.clone();
+			let rem = quotient.div_rem_limb(d);
+			return (quotient, Self::from(rem));
+		}
+
+		// binary long division, slow but simple
+		let mut quotient = Self {
+			limbs: vec![0; self.limbs.len()],
+		};
+		let mut rem = Self::zero();
+
+		for bit in (0..self.limbs.len() * 64).rev() {
+			rem.shl1();
+			if self.limbs[bit / 64] >> (bit % 64) & 1 == 1 {
+				rem += &Self::from(1u64);
+			}
+
+			if rem >= *divisor {
+				rem -= divisor;
+				quotient.limbs[bit / 64] |= 1 << (bit % 64);
+			}
+		}
+
+		quotient.trim();
+		(quotient, rem)
+	}
+
+	fn shl1(&mut self) {
+		let mut carry = 0;
+
+		for limb in &mut self.limbs {
+			let next = *limb >> 63;
+			*limb = *limb << 1 | carry;
+			carry = next;
+		}
+
+		if carry != 0 {
+			self.limbs.push(carry);
+		}
+	}
+
+	/// `self = self * m + a`
+	fn mul_add_limb(&mut self, m: u64, a: u64) {
+		let mut carry = a as u128;
+
+		for limb in &mut self.limbs {
+			let n = *limb as u128 * m as u128 + carry;
+			*limb = n as u64;
+			carry = n >> 64;
+		}
+
+		if carry != 0 {
+			self.limbs.push(carry as u64);
+		}
+		self.trim();
+	}
+
+	/// The closest float, which is infinite if the value is too large.
+	pub fn to_f64(&self) -> f64 {
+		self.limbs
+			.iter()
+			.rev()
+			.fold(0.0, |acc, &limb| acc * 2f64.powi(64) + limb as f64)
+	}
+
+	/// Divides in place by a single limb, returning the remainder.
+	fn div_rem_limb(&mut self, divisor: u64) -> u64 {
+		let mut rem = 0u128;
+
+		for limb in self.limbs.iter_mut().rev() {
+			let n = (rem << 64) | *limb as u128;
+			*limb = (n / divisor as u128) as u64;
+			rem = n % divisor as u128;
+		}
+
+		self.trim();
+		rem as u64
+	}
+
+	fn trim(&mut self) {
+		while self.limbs.last() == Some(&0) {
+			self.limbs.pop();
+		}
+	}
+}
+
+impl From<u64> for BigUint {
+	fn from(n: u64) -> Self {
+		let mut big = Self { limbs: vec![n] };
+		big.trim();
+		big
+	}
+}
+
+impl From<u128> for BigUint {
+	fn from(n: u128) -> Self {
+		let mut big = Self {
+			limbs: vec![n as u64, (n >> 64) as u64],
+		};
+		big.trim();
+		big
+	}
+}
+
+impl AddAssign<&BigUint> for BigUint {
+	fn add_assign(&mut self, other: &BigUint) {
+		if self.limbs.len() < other.limbs.len() {
+			self.limbs.resize(other.limbs.len(), 0);
+		}
+
+		let mut carry = false;
+		for (i, limb) in self.limbs.iter_mut().enumerate() {
+			let rhs = other.limbs.get(i).copied().unwrap_or(0);
+			if !carry && i >= other.limbs.len() {
+				break;
+			}
+
+			let (sum, c1) = limb.overflowing_add(rhs);
+			let (sum, c2) = sum.overflowing_add(carry as u64);
+			*limb = sum;
+			carry = c1 || c2;
+		}
+
+		if carry {
+			self.limbs.push(1);
+		}
+	}
+}
+
+impl Add<&BigUint> for &BigUint {
+	type Output = BigUint;
+
+	fn add(self, other: &BigUint) -> BigUint {
+		let mut sum = self.clone();
+		sum += other;
+		sum
+	}
+}
+
+/// ## Panics
+/// If `other` is larger than `self`.
+impl SubAssign<&BigUint> for BigUint {
+	fn sub_assign(&mut self, other: &BigUint) {
+		assert!(*self >= *other, "subtraction overflow");
+
+		let mut borrow = false;
+		for (i, limb) in self.limbs.iter_mut().enumerate() {
+			let rhs = other.limbs.get(i).copied().unwrap_or(0);
+			if !borrow && i >= other.limbs.len() {
+				break;
+			}
+
+			let (diff, b1) = limb.overflowing_sub(rhs);
+			let (diff, b2) = diff.overflowing_sub(borrow as u64);
+			*limb = diff;
+			borrow = b1 || b2;
+		}
+
+		self.trim();
+	}
+}
+
+impl Sub<&BigUint> for &BigUint {
+	type Output = BigUint;
+
+	fn sub(self, other: &BigUint) -> BigUint {
+		let mut diff = self.clone();
+		diff -= other;
+		diff
+	}
+}
+
+impl Mul<&BigUint> for &BigUint {
+	type Output = BigUint;
+
+	fn mul(self, other: &BigUint) -> BigUint {
+		let mut limbs = vec![0; self.limbs.len() + other.limbs.len()];
+
+		for (i, &a) in self.limbs.iter().enumerate() {
+			let mut carry = 0u128;
+			for (j, &b) in other.limbs.iter().enumerate() {
+				let n = limbs[i + j] as u128 + a as u128 * b as u128 + carry;
+				limbs[i + j] = n as u64;
+				carry = n >> 64;
+			}
+			limbs[i + other.limbs.len()] = carry as u64;
+		}
+
+		let mut product = BigUint { limbs };
+		product.trim();
+		product
+	}
+}
+
+impl Div<&BigUint> for &BigUint {
+	type Output = BigUint;
+
+	fn div(self, other: &BigUint) -> BigUint {
+		self.div_rem(other).0
+	}
+}
+
+impl Rem<&BigUint> for &BigUint {
+	type Output = BigUint;
+
+	fn rem(self, other: &BigUint) -> BigUint {
+		self.div_rem(other).1
+	}
+}
+
+impl Ord for BigUint {
+	fn cmp(&self, other: &Self) -> std::cmp::Ordering {
+		// without trailing zeros more limbs always means a larger number
+		self.limbs
+			.len()
+			.cmp(&other.limbs.len())
+			.then_with(|| self.limbs.iter().rev().cmp(other.limbs.iter().rev()))
+	}
+}
+
+impl PartialOrd for BigUint {
+	fn partial_cmp(&self, other: &Self) -> Option<std::cmp::Ordering> {
+		Some(self.cmp(other))
+	}
+}
+
+impl fmt::Display for BigUint {
+	fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+		const CHUNK: u64 = 10_000_000_000_000_000_000;
+
+		// split into chunks of 19 decimal digits, least significant first
+		let mut n = self.clone();
+		let mut chunks = Vec::new();
+		while !n.is_zero() {
+			chunks.push(n.div_rem_limb(CHUNK));
+		}
+
+		let mut s = chunks.pop().unwrap_or(0).to_string();
+		for chunk in chunks.iter().rev() {
+			s += &format!("{chunk:019}");
+		}
+
+		f.pad_integral(true, "", &s)
+	}
+}
+
+#[test]
+fn test_add() {
+	let max = BigUint::from(u64::MAX);
+	assert_eq!(&max + &BigUint::from(1u64), BigUint::from(1u128 << 64));
+	assert_eq!(
+		(&max + &max).to_string(),
+		(u64::MAX as u128 * 2).to_string()
+	);
+
+	let mut n = BigUint::from(u128::MAX);
+	n += &BigUint::from(1u64);
+	assert_eq!(n.to_string(), "340282366920938463463374607431768211456");
+	assert_eq!(n.to_u64(), None);
+
+	assert_eq!(BigUint::zero().to_string(), "0");
+	assert_eq!(BigUint::from(0u128), BigUint::zero());
+	assert_eq!(BigUint::from(42u64).to_u64(), Some(42));
+	assert_eq!(BigUint::from(1u128 << 100).to_f64(), 2f64.powi(100));
+}
+
+#[test]
+fn test_ord_display() {
+	let small = BigUint::from(u64::MAX);
+	let large = BigUint::from(1u128 << 64);
+	assert!(small < large);
+	assert!(BigUint::zero() < small);
+
+	// a chunk with leading zeros in the middle
+	let n = BigUint::from(10u128.pow(19) * 5 + 7);
+	assert_eq!(n.to_string(), "50000000000000000007");
+	assert_eq!(format!("{n:>22}"), "  50000000000000000007");
+}
+
+#[test]
+fn test_arithmetic() {
+	let big = |s| BigUint::parse(s).unwrap();
+
+	let a = big("123456789012345678901234567890123456789");
+	let b = big("987654321987654321");
+	assert_eq!(a.to_string(), "123456789012345678901234567890123456789");
+
+	let product = &a * &b;
+	assert_eq!(
+		product.to_string(),
+		"121932631246761163237311385323731138532360920590112635269"
+	);
+	assert_eq!(&product / &b, a);
+	assert_eq!(&product % &b, BigUint::zero());
+
+	let (q, r) = (&product + &BigUint::from(5u64)).div_rem(&a);
+	assert_eq!((q, r), (b.clone(), BigUint::from(5u64)));
+
+	assert_eq!(&(&a - &a), &BigUint::zero());
+	assert_eq!(
+		(&a - &b).to_string(),
+		"123456789012345678900246913568135802468"
+	);
+	assert_eq!(
+		big("340282366920938463463374607431768211455").to_u128(),
+		Some(u128::MAX)
+	);
+	assert_eq!(big("+007"), BigUint::from(7u64));
+
+	assert_eq!(BigUint::parse(""), None);
+	assert_eq!(BigUint::parse("12a"), None);
+	assert_eq!(BigUint::parse("-1"), None);
+}
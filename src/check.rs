@@ -0,0 +1,78 @@
+/// A small xorshift random number generator, good enough for test inputs.
+#[derive(Debug, Clone)]
+pub struct Rng(u64);
+
+impl Rng {
+	/// ## Panics
+	/// If `seed` is zero, which would only ever produce zeros.
+	pub fn new(seed: u64) -> Self {
+		assert!(seed != 0, "seed must not be zero");
+		Self(seed)
+	}
+
+	pub fn next_u64(&mut self) -> u64 {
+		self.0 ^= self.0 << 13;
+		self.0 ^= self.0 >> 7;
+		self.0 ^= self.0 << 17;
+		self.0
+	}
+
+	/// A number in `0..n`, slightly biased for huge `n`.
+	///
+	/// ## Panics
+	/// If `n` is zero.
+	pub fn below(&mut self, n: u64) -> u64 {
+		self.next_u64() % n
+	}
+}
+
+/// Greedily shrinks a failing `input`.
+///
+/// `simpler` returns smaller variants of an input, the first one which still
+/// `fails` replaces the input until none of them fails anymore. To terminate
+/// every variant has to be strictly smaller in some sense.
+pub fn shrink<T>(
+	mut input: T,
+	simpler: impl Fn(&T) -> Vec<T>,
+	fails: impl Fn(&T) -> bool,
+) -> T {
+	while let Some(smaller) = simpler(&input).into_iter().find(&fails) {
+		input = smaller;
+	}
+
+	input
+}
+
+#[test]
+fn test_shrink() {
+	// the smallest list with a sum of at least 10
+	let simpler = |v: &Vec<u32>| {
+		let mut variants = Vec::new();
+		for i in 0..v.len() {
+			let mut removed = v.clone();
+			removed.remove(i);
+			variants.push(removed);
+
+			if v[i] > 0 {
+				let mut decreased = v.clone();
+				decreased[i] -= 1;
+				variants.push(decreased);
+			}
+		}
+		variants
+	};
+
+	let shrunk = shrink(vec![3, 8, 1, 9, 4], simpler, |v| {
+		v.iter().sum::<u32>() >= 10
+	});
+	assert_eq!(shrunk.iter().sum::<u32>(), 10);
+	assert_eq!(shrunk.len(), 2);
+}
+
+#[test]
+fn test_rng() {
+	let mut rng = Rng::new(42);
+	let values = (0..1000).map(|_| rng.below(10)).collect::<Vec<_>>();
+	assert!((0..10).all(|n| values.contains(&n)));
+	assert_ne!(Rng::new(1).next_u64(), Rng::new(2).next_u64());
+}
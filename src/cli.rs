@@ -0,0 +1,303 @@
+use std::{fmt, path::PathBuf};
+
+use adventofcode_2025::{input::InputSource, solution::Part};
+
+pub const USAGE: &str = "\
+Usage: aoc <command> [options] [days...]
+
+Commands:
+  run      Runs the given days, all days if none are given
+  verify   Compares the answers against the known ones
+  bench    Measures parsing and solving of the given days
+  help     Prints this message
+
+Options:
+  --part <1|2>          Only run the given part
+  --input <path|->      Read the input from a file or stdin (-) instead
+                        of the baked in one, only valid for a single day
+  --answers <path>      (verify) Read the known answers from a file instead
+                        of the baked in answers.txt
+  --runs <n>            (bench) How many times each phase is run [default: 20]
+  --json                (bench) Print the timings as JSON
+
+Days can be given as single numbers (5), ranges (1..3, 1..=7, 4..)
+or comma separated lists of those (1,3,5..=7).";
+
+/// Which days and parts to run against which input.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Selection {
+	pub days: Vec<u32>,
+	pub part: Option<Part>,
+	pub input: InputSource,
+}
+
+impl Selection {
+	pub fn parts(&self) -> Vec<Part> {
+		match self.part {
+			Some(part) => vec![part],
+			None => Part::ALL.to_vec(),
+		}
+	}
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Command {
+	Run(Selection),
+	Verify {
+		selection: Selection,
+		answers: Option<PathBuf>,
+	},
+	Bench {
+		selection: Selection,
+		runs: usize,
+		json: bool,
+	},
+	Help,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CliError(String);
+
+impl fmt::Display for CliError {
+	fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+		f.write_str(&self.0)
+	}
+}
+
+macro_rules! cli_err {
+	($($arg:tt)*) => {
+		CliError(format!($($arg)*))
+	};
+}
+
+/// Parses the command line arguments (without the binary name).
+///
+/// `max_day` is used for open ended ranges like `4..`.
+pub fn parse_args(
+	args: impl IntoIterator<Item = String>,
+	max_day: u32,
+) -> Result<Command, CliError> {
+	let mut args = args.into_iter();
+
+	let cmd = match args.next() {
+		Some(c) if matches!(c.as_str(), "run" | "verify" | "bench") => c,
+		Some(c) if c != "help" && c != "-h" && c != "--help" => {
+			return Err(cli_err!("unknown command `{c}`"));
+		}
+		_ => return Ok(Command::Help),
+	};
+
+	let mut days = Vec::new();
+	let mut part = None;
+	let mut input = InputSource::Default;
+	let mut answers = None;
+	let mut runs = 20;
+	let mut json = false;
+
+	while let Some(arg) = args.next() {
+		let mut value =
+			|| args.next().ok_or_else(|| cli_err!("{arg} expects a value"));
+
+		match arg.as_str() {
+			"--part" | "-p" => part = Some(parse_part(&value()?)?),
+			"--input" | "-i" => input = InputSource::from_arg(&value()?),
+			"--answers" if cmd == "verify" => {
+				answers = Some(PathBuf::from(value()?));
+			}
+			"--runs" if cmd == "bench" => {
+				let val = value()?;
+				runs =
+					val.parse().ok().filter(|&r| r > 0).ok_or_else(|| {
+						cli_err!("invalid number of runs `{val}`")
+					})?;
+			}
+			"--json" if cmd == "bench" => json = true,
+			a if a.starts_with('-') => {
+				return Err(cli_err!("unknown option `{a}`"));
+			}
+			a => parse_days(a, max_day, &mut days)?,
+		}
+	}
+
+	if days.is_empty() {
+		days.extend(1..=max_day);
+	}
+
+	days.sort_unstable();
+	days.dedup();
+
+	if !input.is_default() && days.len() != 1 {
+		return Err(cli_err!("--input requires exactly one day"));
+	}
+
+	let selection = Selection { days, part, input };
+
+	Ok(match cmd.as_str() {
+		"verify" => Command::Verify { selection, answers },
+		"bench" => Command::Bench {
+			selection,
+			runs,
+			json,
+		},
+		_ => Command::Run(selection),
+	})
+}
+
+fn parse_part(s: &str) -> Result<Part, CliError> {
+	s.parse()
+		.ok()
+		.and_then(Part::new)
+		.ok_or_else(|| cli_err!("invalid part `{s}`, expected 1 or 2"))
+}
+
+fn parse_day(s: &str) -> Result<u32, CliError> {
+	s.parse().map_err(|_| cli_err!("invalid day `{s}`"))
+}
+
+/// Parses a day specification like `5`, `1..=7` or `1,3..5` into `days`.
+fn parse_days(
+	spec: &str,
+	max_day: u32,
+	days: &mut Vec<u32>,
+) -> Result<(), CliError> {
+	for part in spec.split(',').filter(|p| !p.is_empty()) {
+		let Some((start, end)) = part.split_once("..") else {
+			days.push(parse_day(part)?);
+			continue;
+		};
+
+		let start = if start.is_empty() {
+			1
+		} else {
+			parse_day(start)?
+		};
+
+		let end = match end.strip_prefix('=') {
+			Some(end) => parse_day(end)?,
+			None if end.is_empty() => max_day,
+			None => parse_day(end)?
+				.checked_sub(1)
+				.ok_or_else(|| cli_err!("invalid range `{part}`"))?,
+		};
+
+		if start > end {
+			return Err(cli_err!("empty range `{part}`"));
+		}
+
+		days.extend(start..=end);
+	}
+
+	Ok(())
+}
+
+#[cfg(test)]
+fn parse(args: &str) -> Result<Command, CliError> {
+	parse_args(args.split_whitespace().map(String::from), 7)
+}
+
+#[test]
+fn test_run() {
+	assert_eq!(
+		parse("run 5"),
+		Ok(Command::Run(Selection {
+			days: vec![5],
+			part: None,
+			input: InputSource::Default,
+		}))
+	);
+	assert_eq!(
+		parse("run 1..=7"),
+		Ok(Command::Run(Selection {
+			days: (1..=7).collect(),
+			part: None,
+			input: InputSource::Default,
+		}))
+	);
+	assert_eq!(
+		parse("run --part 2 3"),
+		Ok(Command::Run(Selection {
+			days: vec![3],
+			part: Some(Part::Two),
+			input: InputSource::Default,
+		}))
+	);
+	assert_eq!(
+		parse("run 6,1..3 5.."),
+		Ok(Command::Run(Selection {
+			days: vec![1, 2, 5, 6, 7],
+			part: None,
+			input: InputSource::Default,
+		}))
+	);
+	assert_eq!(
+		parse("run"),
+		Ok(Command::Run(Selection {
+			days: (1..=7).collect(),
+			part: None,
+			input: InputSource::Default,
+		}))
+	);
+}
+
+#[test]
+fn test_input() {
+	assert_eq!(
+		parse("run 1 --input -"),
+		Ok(Command::Run(Selection {
+			days: vec![1],
+			part: None,
+			input: InputSource::Stdin,
+		}))
+	);
+	assert_eq!(
+		parse("run -i inputs/day2.txt 2"),
+		Ok(Command::Run(Selection {
+			days: vec![2],
+			part: None,
+			input: InputSource::File("inputs/day2.txt".into()),
+		}))
+	);
+}
+
+#[test]
+fn test_verify() {
+	assert_eq!(
+		parse("verify 1..=2 --answers answers.txt"),
+		Ok(Command::Verify {
+			selection: Selection {
+				days: vec![1, 2],
+				part: None,
+				input: InputSource::Default,
+			},
+			answers: Some("answers.txt".into()),
+		})
+	);
+}
+
+#[test]
+fn test_bench() {
+	assert_eq!(
+		parse("bench 6 --runs 100 --json --part 2"),
+		Ok(Command::Bench {
+			selection: Selection {
+				days: vec![6],
+				part: Some(Part::Two),
+				input: InputSource::Default,
+			},
+			runs: 100,
+			json: true,
+		})
+	);
+}
+
+#[test]
+fn test_errors() {
+	assert!(parse("run 5..2").is_err());
+	assert!(parse("run --part 3").is_err());
+	assert!(parse("run five").is_err());
+	assert!(parse("walk").is_err());
+	assert!(parse("run --answers answers.txt").is_err());
+	assert!(parse("bench --runs 0").is_err());
+	assert!(parse("run --json").is_err());
+	assert!(parse("run 1..=2 --input day1.txt").is_err());
+}
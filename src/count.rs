@@ -0,0 +1,212 @@
+use std::{error::Error, fmt};
+
+use crate::bigint::BigUint;
+
+/// A number used to count things which might get too large for its type.
+pub trait Count: Clone + fmt::Debug + fmt::Display {
+	/// Name used in [`Overflow`] errors.
+	const NAME: &'static str;
+
+	fn zero() -> Self;
+
+	fn one() -> Self;
+
+	/// Returns `None` if the sum does not fit.
+	fn checked_add(&self, other: &Self) -> Option<Self>;
+
+	/// The closest float, used to visualize counts.
+	fn to_f64(&self) -> f64;
+
+	fn try_add(&self, other: &Self) -> Result<Self, Overflow> {
+		self.checked_add(other).ok_or(Overflow { name: Self::NAME })
+	}
+
+	fn try_sum<'a>(
+		values: impl IntoIterator<Item = &'a Self>,
+	) -> Result<Self, Overflow>
+	where
+		Self: 'a,
+	{
+		values
+			.into_iter()
+			.try_fold(Self::zero(), |sum, value| sum.try_add(value))
+	}
+}
+
+/// A count got too large for its type.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Overflow {
+	pub name: &'static str,
+}
+
+impl fmt::Display for Overflow {
+	fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+		write!(f, "count overflowed `{}`", self.name)
+	}
+}
+
+impl Error for Overflow {}
+
+impl Count for u64 {
+	const NAME: &'static str = "u64";
+
+	fn zero() -> Self {
+		0
+	}
+
+	fn one() -> Self {
+		1
+	}
+
+	fn checked_add(&self, other: &Self) -> Option<Self> {
+		u64::checked_add(*self, *other)
+	}
+
+	fn to_f64(&self) -> f64 {
+		*self as f64
+	}
+}
+
+impl Count for u128 {
+	const NAME: &'static str = "u128";
+
+	fn zero() -> Self {
+		0
+	}
+
+	fn one() -> Self {
+		1
+	}
+
+	fn checked_add(&self, other: &Self) -> Option<Self> {
+		u128::checked_add(*self, *other)
+	}
+
+	fn to_f64(&self) -> f64 {
+		*self as f64
+	}
+}
+
+/// Never overflows.
+impl Count for BigUint {
+	const NAME: &'static str = "BigUint";
+
+	fn zero() -> Self {
+		BigUint::zero()
+	}
+
+	fn one() -> Self {
+		BigUint::from(1u64)
+	}
+
+	fn checked_add(&self, other: &Self) -> Option<Self> {
+		Some(self + other)
+	}
+
+	fn to_f64(&self) -> f64 {
+		BigUint::to_f64(self)
+	}
+}
+
+/// Counts modulo `p`, which never overflows.
+///
+/// `p` is usually a large prime so results of different runs can be
+/// compared without the cost of a [`BigUint`]. It's chosen at runtime with
+/// [`Modulo::new`], [`Count::zero`] and [`Count::one`] work for any modulus
+/// and take it from the other count when added to one.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Modulo {
+	value: u64,
+	/// `None` for zero and one, which are the same for every modulus
+	p: Option<u64>,
+}
+
+impl Modulo {
+	/// ## Panics
+	/// If `p` is zero.
+	pub fn new(n: u64, p: u64) -> Self {
+		assert!(p > 0, "modulus must not be zero");
+		Self {
+			value: n % p,
+			p: Some(p),
+		}
+	}
+
+	pub fn value(self) -> u64 {
+		self.value
+	}
+
+	/// `None` if the count never met a [`Modulo::new`].
+	pub fn modulus(self) -> Option<u64> {
+		self.p
+	}
+}
+
+impl fmt::Display for Modulo {
+	fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+		self.value.fmt(f)
+	}
+}
+
+impl Count for Modulo {
+	const NAME: &'static str = "Modulo";
+
+	fn zero() -> Self {
+		Self { value: 0, p: None }
+	}
+
+	fn one() -> Self {
+		Self { value: 1, p: None }
+	}
+
+	/// ## Panics
+	/// If both counts have a different modulus.
+	fn checked_add(&self, other: &Self) -> Option<Self> {
+		let p = match (self.p, other.p) {
+			(Some(p), Some(q)) => {
+				assert_eq!(p, q, "can't add counts of different moduli");
+				Some(p)
+			}
+			(p, None) | (None, p) => p,
+		};
+
+		let value = match p {
+			Some(p) => {
+				((self.value as u128 + other.value as u128) % p as u128) as u64
+			}
+			// only sums of zeros and ones so far
+			None => self.value.checked_add(other.value)?,
+		};
+		Some(Self { value, p })
+	}
+
+	/// Only the remainder, the actual count is unknown.
+	fn to_f64(&self) -> f64 {
+		self.value as f64
+	}
+}
+
+#[test]
+fn test_counts() {
+	assert_eq!(u64::MAX.try_add(&1), Err(Overflow { name: "u64" }));
+	assert_eq!(
+		u64::try_sum(&[u64::MAX - 1, 1]).map_err(|e| e.to_string()),
+		Ok(u64::MAX)
+	);
+	assert_eq!(
+		u64::try_sum(&[u64::MAX, 1]).map_err(|e| e.to_string()),
+		Err("count overflowed `u64`".to_string())
+	);
+	assert_eq!(u128::try_sum(&[u64::MAX as u128, 1]), Ok(1 << 64));
+
+	let a = Modulo::new(u64::MAX - 60, u64::MAX - 58);
+	assert_eq!(a.try_add(&a).map(Modulo::value), Ok(u64::MAX - 62));
+	assert_eq!(Modulo::try_sum(&[Modulo::new(5, 7); 3]).unwrap().value(), 1);
+
+	// the modulus comes from the count it's added to
+	let p = "1000000007".parse().unwrap();
+	let sum = Modulo::one().try_add(&Modulo::new(p - 1, p)).unwrap();
+	assert_eq!((sum.value(), sum.modulus()), (0, Some(p)));
+	let ones = Modulo::try_sum(&[Modulo::one(); 3]).unwrap();
+	assert_eq!((ones.value(), ones.modulus()), (3, None));
+}
@@ -1,35 +1,58 @@
-const INPUT: &str = include_str!("../inputs/day1.txt");
+use std::fmt::Write;
 
-const MAX: i32 = 100;
+use crate::{
+	bench::{self, DayTimings},
+	check::{self, Rng},
+	parse::ParseError,
+	solution::{Part, Solution, SolveError},
+};
+
+pub const INPUT: &str = include_str!("../inputs/day1.txt");
+
+pub const MAX: i32 = 100;
 
 #[derive(Debug)]
-struct Dial {
+pub struct Dial {
 	pub idx: i32,
+	size: i32,
 }
 
 impl Dial {
-	fn new(idx: i32) -> Self {
-		Self { idx }
+	/// A dial with the puzzle's [`MAX`] positions.
+	pub fn new(idx: i32) -> Self {
+		Self::with_size(MAX, idx)
+	}
+
+	/// A dial with the positions `0..size`.
+	///
+	/// ## Panics
+	/// If `idx` is not one of the positions.
+	pub fn with_size(size: i32, idx: i32) -> Self {
+		assert!((0..size).contains(&idx), "position outside of the dial");
+		Self { idx, size }
+	}
+
+	pub fn size(&self) -> i32 {
+		self.size
 	}
 
-	fn is_zero(&self) -> bool {
+	pub fn is_zero(&self) -> bool {
 		self.idx == 0
 	}
 
-	fn rotate(&mut self, dir: Dir, steps: u32) {
-		match dir {
-			Dir::Left => {
-				self.idx = (self.idx - steps as i32).rem_euclid(MAX);
-			}
-			Dir::Right => {
-				self.idx = (self.idx + steps as i32) % MAX;
-			}
-		}
+	pub fn rotate(&mut self, dir: Dir, steps: u32) {
+		// steps don't fit into an i32
+		let (idx, steps) = (i64::from(self.idx), i64::from(steps));
+		let moved = match dir {
+			Dir::Left => idx - steps,
+			Dir::Right => idx + steps,
+		};
+		self.idx = moved.rem_euclid(self.size.into()) as i32;
 	}
 
-	fn rotate_with_count(&mut self, dir: Dir, steps: u32) -> u32 {
-		let mut count = steps / MAX as u32;
-		let rem_steps = steps % MAX as u32;
+	pub fn rotate_with_count(&mut self, dir: Dir, steps: u32) -> u32 {
+		let mut count = steps / self.size as u32;
+		let rem_steps = steps % self.size as u32;
 
 		match dir {
 			Dir::Left => {
@@ -40,7 +63,7 @@ impl Dial {
 				}
 
 				if n_idx < 0 {
-					self.idx = MAX + n_idx;
+					self.idx = self.size + n_idx;
 				} else {
 					self.idx = n_idx;
 				}
@@ -48,9 +71,9 @@ impl Dial {
 			Dir::Right => {
 				let n_idx = self.idx + rem_steps as i32;
 
-				if n_idx >= MAX {
+				if n_idx >= self.size {
 					count += 1;
-					self.idx = n_idx - MAX;
+					self.idx = n_idx - self.size;
 				} else {
 					self.idx = n_idx;
 				}
@@ -59,35 +82,361 @@ impl Dial {
 
 		count
 	}
+
+	/// Moves the dial by a single click.
+	pub fn click(&mut self, dir: Dir) {
+		self.idx = match dir {
+			Dir::Left => (self.idx + self.size - 1) % self.size,
+			Dir::Right => (self.idx + 1) % self.size,
+		};
+	}
+
+	/// The same as [`Dial::rotate_with_count`] but clicking through every
+	/// step, which is slow but obviously correct.
+	pub fn rotate_by_clicks(&mut self, dir: Dir, steps: u32) -> u32 {
+		let mut count = 0;
+
+		for _ in 0..steps {
+			self.click(dir);
+			count += self.is_zero() as u32;
+		}
+
+		count
+	}
+
+	/// How often the dial would point at `target` when rotating, including
+	/// where it stops but not where it starts.
+	///
+	/// ## Panics
+	/// If `target` is outside of the dial.
+	pub fn passes(&self, dir: Dir, steps: u32, target: i32) -> u64 {
+		assert!(
+			(0..self.size).contains(&target),
+			"target outside of the dial"
+		);
+		let size = self.size as u64;
+		let distance = match dir {
+			Dir::Left => (self.idx - target).rem_euclid(self.size),
+			Dir::Right => (target - self.idx).rem_euclid(self.size),
+		} as u64;
+		// being on the target already means a full turn until the next time
+		let first = if distance == 0 { size } else { distance };
+
+		match (steps as u64).checked_sub(first) {
+			Some(rest) => rest / size + 1,
+			None => 0,
+		}
+	}
+
+	/// Rotates and records all hits of the targets.
+	pub fn rotate_with_hits(&mut self, dir: Dir, steps: u32, hits: &mut Hits) {
+		for (i, &target) in hits.targets.iter().enumerate() {
+			hits.passes_through[i] += self.passes(dir, steps, target);
+		}
+
+		self.rotate(dir, steps);
+
+		if let Ok(i) = hits.targets.binary_search(&self.idx) {
+			hits.ends_on[i] += 1;
+		}
+	}
+}
+
+/// How often a dial hit each of a set of target positions.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Hits {
+	/// sorted and without duplicates
+	targets: Vec<i32>,
+	ends_on: Vec<u64>,
+	passes_through: Vec<u64>,
+}
+
+impl Hits {
+	/// No hits yet of `targets` on a dial with `size` positions.
+	///
+	/// ## Panics
+	/// If any target is outside of the dial.
+	pub fn new(size: i32, targets: impl IntoIterator<Item = i32>) -> Self {
+		let mut targets = targets.into_iter().collect::<Vec<_>>();
+		assert!(
+			targets.iter().all(|t| (0..size).contains(t)),
+			"target outside of the dial"
+		);
+		targets.sort_unstable();
+		targets.dedup();
+
+		Self {
+			ends_on: vec![0; targets.len()],
+			passes_through: vec![0; targets.len()],
+			targets,
+		}
+	}
+
+	/// Rotates `dial` by every instruction counting the hits of `targets`.
+	pub fn count(
+		dial: &mut Dial,
+		input: &[(Dir, u32)],
+		targets: impl IntoIterator<Item = i32>,
+	) -> Self {
+		let mut hits = Self::new(dial.size(), targets);
+		for &(dir, steps) in input {
+			dial.rotate_with_hits(dir, steps, &mut hits);
+		}
+		hits
+	}
+
+	pub fn targets(&self) -> &[i32] {
+		&self.targets
+	}
+
+	/// How often a rotation stopped at `target`, `None` if it's no target.
+	pub fn ends_on(&self, target: i32) -> Option<u64> {
+		let i = self.targets.binary_search(&target).ok()?;
+		Some(self.ends_on[i])
+	}
+
+	/// How often the dial pointed at `target` during any rotation, `None` if
+	/// it's no target.
+	pub fn passes_through(&self, target: i32) -> Option<u64> {
+		let i = self.targets.binary_search(&target).ok()?;
+		Some(self.passes_through[i])
+	}
 }
 
-#[derive(Debug, Copy, Clone)]
-enum Dir {
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum Dir {
 	Left,
 	Right,
 }
 
-fn parse_input(input: &str) -> impl Iterator<Item = (Dir, u32)> {
-	input.trim().lines().map(|l| {
-		let l = l.trim();
-		// first comes a char
-		let dir = match l.chars().next().unwrap() {
-			'L' => Dir::Left,
-			'R' => Dir::Right,
-			c => panic!("Unknown direction char: {c}"),
+/// The position of a dial after every instruction.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Trace {
+	size: i32,
+	start: i32,
+	steps: Vec<TraceStep>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct TraceStep {
+	pub dir: Dir,
+	pub steps: u32,
+	/// where the dial points after the instruction
+	pub position: i32,
+}
+
+impl Trace {
+	/// Rotates `dial` by every instruction recording where it stops.
+	pub fn record(dial: &mut Dial, input: &[(Dir, u32)]) -> Self {
+		let start = dial.idx;
+		let steps = input
+			.iter()
+			.map(|&(dir, steps)| {
+				dial.rotate(dir, steps);
+				TraceStep {
+					dir,
+					steps,
+					position: dial.idx,
+				}
+			})
+			.collect();
+
+		Self {
+			size: dial.size(),
+			start,
+			steps,
+		}
+	}
+
+	pub fn start(&self) -> i32 {
+		self.start
+	}
+
+	pub fn steps(&self) -> &[TraceStep] {
+		&self.steps
+	}
+
+	/// How often the dial stopped at each position, indexed by position.
+	pub fn histogram(&self) -> Vec<u64> {
+		let mut histogram = vec![0; self.size as usize];
+		for step in &self.steps {
+			histogram[step.position as usize] += 1;
+		}
+		histogram
+	}
+
+	/// The most instructions in a row which did not stop at zero.
+	pub fn longest_run_without_zero(&self) -> usize {
+		self.steps
+			.split(|step| step.position == 0)
+			.map(<[_]>::len)
+			.max()
+			.unwrap_or(0)
+	}
+
+	/// The total number of steps turned to `dir`.
+	pub fn distance(&self, dir: Dir) -> u64 {
+		self.steps
+			.iter()
+			.filter(|step| step.dir == dir)
+			.map(|step| step.steps as u64)
+			.sum()
+	}
+
+	/// One line per instruction with a header, the start is instruction 0.
+	pub fn to_csv(&self) -> String {
+		let mut csv = format!(
+			"instruction,direction,steps,position\n0,,0,{}\n",
+			self.start
+		);
+
+		for (i, step) in self.steps.iter().enumerate() {
+			let dir = match step.dir {
+				Dir::Left => 'L',
+				Dir::Right => 'R',
+			};
+			writeln!(csv, "{},{dir},{},{}", i + 1, step.steps, step.position)
+				.unwrap();
+		}
+
+		csv
+	}
+}
+
+/// A dial and the instructions to rotate it by, used to compare rotation
+/// implementations.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Case {
+	pub size: i32,
+	pub start: i32,
+	pub instructions: Vec<(Dir, u32)>,
+}
+
+impl Case {
+	/// Mostly small dials with up to a few full turns per instruction, which
+	/// makes the edge cases likely.
+	pub fn random(rng: &mut Rng) -> Self {
+		let size = match rng.below(4) {
+			0 => MAX,
+			_ => 1 + rng.below(12) as i32,
 		};
-		let num = l[1..].parse().expect("Failed to parse number");
+		let instructions = (0..1 + rng.below(8))
+			.map(|_| {
+				let dir = [Dir::Left, Dir::Right][rng.below(2) as usize];
+				(dir, rng.below(3 * size as u64 + 2) as u32)
+			})
+			.collect();
+
+		Self {
+			size,
+			start: rng.below(size as u64) as i32,
+			instructions,
+		}
+	}
+
+	/// The first instruction after which `rotate` disagrees with
+	/// [`Dial::rotate_by_clicks`] on the count or the position.
+	pub fn diverges(
+		&self,
+		rotate: impl Fn(&mut Dial, Dir, u32) -> u32,
+	) -> Option<usize> {
+		let mut fast = Dial::with_size(self.size, self.start);
+		let mut slow = Dial::with_size(self.size, self.start);
+
+		self.instructions.iter().position(|&(dir, steps)| {
+			let count = rotate(&mut fast, dir, steps);
+			count != slow.rotate_by_clicks(dir, steps) || fast.idx != slow.idx
+		})
+	}
+
+	/// Variants with fewer instructions, fewer steps, a smaller start or a
+	/// smaller dial.
+	fn simpler(&self) -> Vec<Self> {
+		let mut variants = Vec::new();
+		let mut push = |f: &dyn Fn(&mut Self)| {
+			let mut case = self.clone();
+			f(&mut case);
+			variants.push(case);
+		};
+
+		for i in 0..self.instructions.len() {
+			push(&|c| {
+				c.instructions.remove(i);
+			});
+
+			let steps = self.instructions[i].1;
+			if steps > 0 {
+				push(&|c| c.instructions[i].1 = steps / 2);
+				push(&|c| c.instructions[i].1 = steps - 1);
+			}
+		}
+
+		if self.start > 0 {
+			push(&|c| c.start = 0);
+			push(&|c| c.start -= 1);
+		}
+		if self.start + 1 < self.size {
+			push(&|c| c.size -= 1);
+			// keep the number of turns about the same on the smaller dial
+			push(&|c| {
+				for (_, steps) in &mut c.instructions {
+					*steps = (*steps as u64 * (c.size as u64 - 1)
+						/ c.size as u64) as u32;
+				}
+				c.size -= 1;
+			});
+		}
 
-		(dir, num)
-	})
+		variants
+	}
 }
 
-fn part1() -> u32 {
-	let input = parse_input(INPUT);
+/// Compares `rotate` against clicking through every step on `cases` random
+/// cases, returning a minimal failing case if they ever disagree.
+pub fn check_rotation(
+	cases: usize,
+	rng: &mut Rng,
+	rotate: impl Fn(&mut Dial, Dir, u32) -> u32,
+) -> Result<(), Case> {
+	for _ in 0..cases {
+		let case = Case::random(rng);
+
+		if case.diverges(&rotate).is_some() {
+			return Err(check::shrink(case, Case::simpler, |c| {
+				c.diverges(&rotate).is_some()
+			}));
+		}
+	}
+
+	Ok(())
+}
+
+pub fn parse_input(input: &str) -> Result<Vec<(Dir, u32)>, ParseError> {
+	input
+		.trim()
+		.lines()
+		.map(|l| {
+			let l = l.trim();
+			// first comes a char
+			let dir = match l.chars().next() {
+				Some('L') => Dir::Left,
+				Some('R') => Dir::Right,
+				_ => return Err(ParseError::at_char(input, l, "`L` or `R`")),
+			};
+			let num = l[1..].parse().map_err(|_| {
+				ParseError::at(input, &l[1..], "a number of steps")
+			})?;
+
+			Ok((dir, num))
+		})
+		.collect()
+}
 
+pub fn part1(input: Vec<(Dir, u32)>) -> u32 {
 	let mut dial = Dial::new(50);
 
 	input
+		.into_iter()
 		.map(|(dir, steps)| {
 			dial.rotate(dir, steps);
 			dial.is_zero() as u32
@@ -95,24 +444,42 @@ fn part1() -> u32 {
 		.sum::<u32>()
 }
 
-fn part2() -> u32 {
-	let input = parse_input(INPUT);
-
+pub fn part2(input: Vec<(Dir, u32)>) -> u32 {
 	let mut dial = Dial::new(50);
 
 	input
+		.into_iter()
 		.map(|(dir, steps)| dial.rotate_with_count(dir, steps))
 		.sum::<u32>()
 }
 
-fn main() {
-	let p1 = part1();
-	println!("Part 1: {p1}");
-	assert_eq!(p1, 1055);
+pub struct Day1;
+
+impl Solution for Day1 {
+	fn day(&self) -> u32 {
+		1
+	}
+
+	fn input(&self) -> &'static str {
+		INPUT
+	}
+
+	fn part1(&self, input: &str) -> Result<u64, SolveError> {
+		Ok(part1(parse_input(input)?).into())
+	}
 
-	let p2 = part2();
-	println!("Part 2: {p2}");
-	assert_eq!(p2, 6386);
+	fn part2(&self, input: &str) -> Result<u64, SolveError> {
+		Ok(part2(parse_input(input)?).into())
+	}
+
+	fn bench(
+		&self,
+		input: &str,
+		runs: usize,
+		parts: &[Part],
+	) -> Result<DayTimings, ParseError> {
+		bench::phases(input, runs, parts, parse_input, part1, part2)
+	}
 }
 
 /*
@@ -143,3 +510,131 @@ fn test_with_count() {
 	assert_eq!(dial.rotate_with_count(Dir::Right, 14), 0);
 	assert_eq!(dial.rotate_with_count(Dir::Left, 82), 1);
 }
+
+#[test]
+fn test_large_steps() {
+	let rotations =
+		parse_input("R4294967295\nL4294967295\nL2147483648").unwrap();
+
+	let mut dial = Dial::new(50);
+	let mut counted = Dial::new(50);
+	let mut ends = Vec::new();
+	for &(dir, steps) in &rotations {
+		dial.rotate(dir, steps);
+		counted.rotate_with_count(dir, steps);
+		assert_eq!(dial.idx, counted.idx);
+		ends.push(dial.idx);
+	}
+	assert_eq!(ends, [45, 50, 2]);
+}
+
+#[test]
+fn test_rotation_equivalence() {
+	let mut rng = Rng::new(0x5eed_d1a1);
+	assert_eq!(
+		check_rotation(1_000_000, &mut rng, Dial::rotate_with_count),
+		Ok(())
+	);
+
+	// forgetting that a full turn from zero to the left ends on zero
+	let broken = |dial: &mut Dial, dir, steps| {
+		let start = dial.idx;
+		let count = dial.rotate_with_count(dir, steps);
+		count - (start == 0 && steps > 0 && dial.idx == 0) as u32
+	};
+	assert_eq!(
+		check_rotation(1000, &mut rng, broken),
+		Err(Case {
+			size: 1,
+			start: 0,
+			instructions: vec![(Dir::Left, 1)],
+		})
+	);
+}
+
+#[test]
+fn test_hits() {
+	let input =
+		parse_input("L68\nL30\nR48\nL5\nR60\nL55\nL1\nL99\nR14\nL82").unwrap();
+
+	let hits = Hits::count(&mut Dial::new(50), &input, [75, 0, 25, 0]);
+	assert_eq!(hits.targets(), [0, 25, 75]);
+	assert_eq!(hits.ends_on(0), Some(part1(input.clone()).into()));
+	assert_eq!(hits.passes_through(0), Some(part2(input.clone()).into()));
+	assert_eq!(hits.ends_on(25), Some(0));
+	assert_eq!(hits.passes_through(25), Some(4));
+	assert_eq!(hits.passes_through(75), Some(4));
+	assert_eq!(hits.passes_through(50), None);
+
+	let mut dial = Dial::with_size(10, 5);
+	let hits = Hits::count(&mut dial, &input, [0, 3]);
+	assert_eq!(dial.idx, 7);
+	assert_eq!(hits.ends_on(0), Some(2));
+	assert_eq!(hits.passes_through(0), Some(46));
+	assert_eq!(hits.passes_through(3), Some(46));
+
+	let dial = Dial::with_size(10, 3);
+	assert_eq!(dial.passes(Dir::Right, 9, 3), 0);
+	assert_eq!(dial.passes(Dir::Right, 10, 3), 1);
+	assert_eq!(dial.passes(Dir::Left, 3, 0), 1);
+	assert_eq!(dial.passes(Dir::Left, 23, 0), 3);
+}
+
+#[test]
+#[should_panic(expected = "target outside of the dial")]
+fn test_passes_outside() {
+	Dial::with_size(10, 3).passes(Dir::Right, 2, 15);
+}
+
+#[test]
+#[should_panic(expected = "target outside of the dial")]
+fn test_hits_outside() {
+	Hits::new(10, [0, 10]);
+}
+
+#[test]
+fn test_trace() {
+	let input =
+		parse_input("L68\nL30\nR48\nL5\nR60\nL55\nL1\nL99\nR14\nL82").unwrap();
+	let trace = Trace::record(&mut Dial::new(50), &input);
+
+	assert_eq!(
+		trace.steps().iter().map(|s| s.position).collect::<Vec<_>>(),
+		[82, 52, 0, 95, 55, 0, 99, 0, 14, 32]
+	);
+
+	let histogram = trace.histogram();
+	assert_eq!(histogram.len(), 100);
+	assert_eq!(histogram[0] as u32, part1(input));
+	assert_eq!(histogram[82], 1);
+	assert_eq!(histogram.iter().sum::<u64>(), 10);
+
+	assert_eq!(trace.longest_run_without_zero(), 2);
+	assert_eq!(trace.distance(Dir::Left), 340);
+	assert_eq!(trace.distance(Dir::Right), 122);
+
+	let csv = trace.to_csv();
+	let lines = csv.lines().collect::<Vec<_>>();
+	assert_eq!(lines.len(), 12);
+	assert_eq!(
+		lines[..3],
+		[
+			"instruction,direction,steps,position",
+			"0,,0,50",
+			"1,L,68,82"
+		]
+	);
+	assert_eq!(lines[11], "10,L,82,32");
+}
+
+#[test]
+fn test_parse_error() {
+	assert_eq!(
+		parse_input("L10\nR5\nX3").unwrap_err(),
+		ParseError::new(3, 1, "X", "`L` or `R`")
+	);
+	assert_eq!(
+		parse_input("L10\nRfive").unwrap_err(),
+		ParseError::new(2, 2, "five", "a number of steps")
+	);
+}
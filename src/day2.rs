@@ -1,34 +1,46 @@
-use std::collections::BTreeSet;
+use std::{collections::BTreeSet, error::Error, fmt};
 
-const INPUT: &str = include_str!("../inputs/day2.txt");
+use crate::{
+	bench::{self, DayTimings},
+	bigint::BigUint,
+	int::{Id, Int},
+	parse::ParseError,
+	solution::{Part, Solution, SolveError},
+};
+
+pub const INPUT: &str = include_str!("../inputs/day2.txt");
 
 #[derive(Debug)]
-struct PatternRepeater {
+pub struct PatternRepeater<T: Id = u64> {
 	// ex: 1, 10, 100
-	pat: u64,
+	pat: T::Wide,
 	digits: u32,
 	repeat: u32,
 	variable_repeat: bool,
 	finished: bool,
 }
 
-impl PatternRepeater {
-	fn new(pat: u64, repeat: u32, variable_repeat: bool) -> Self {
+impl<T: Id> PatternRepeater<T> {
+	pub fn new(pat: T, repeat: u32, variable_repeat: bool) -> Self {
 		Self {
-			pat,
-			digits: pat.ilog10() + 1,
+			digits: pat.digits(&T::from_u64(10)),
+			pat: pat.widen(),
 			repeat,
 			variable_repeat,
 			finished: false,
 		}
 	}
 
+	fn ten() -> T::Wide {
+		T::Wide::from_u64(10)
+	}
+
 	fn add(&mut self) {
-		let n_pat = self.pat + 1;
-		if n_pat >= 10u64.pow(self.digits) {
+		let n_pat = self.pat.add(&T::Wide::from_u64(1));
+		if n_pat >= Self::ten().pow(self.digits) {
 			if self.variable_repeat {
 				self.repeat += 1;
-				self.pat = 10u64.pow(self.digits - 1);
+				self.pat = Self::ten().pow(self.digits - 1);
 			} else {
 				self.finished = true;
 			}
@@ -37,19 +49,20 @@ impl PatternRepeater {
 		}
 	}
 
-	fn value(&self) -> u64 {
-		let mut val = self.pat;
+	/// In the wider type, repeating a pattern may not fit into `T`.
+	pub fn value(&self) -> T::Wide {
+		let mut val = self.pat.clone();
 
 		for _ in 1..self.repeat {
-			val = val * 10u64.pow(self.digits) + self.pat;
+			val = val.mul(&Self::ten().pow(self.digits)).add(&self.pat);
 		}
 
 		val
 	}
 }
 
-impl Iterator for PatternRepeater {
-	type Item = u64;
+impl<T: Id> Iterator for PatternRepeater<T> {
+	type Item = T::Wide;
 
 	fn next(&mut self) -> Option<Self::Item> {
 		if self.finished {
@@ -62,129 +75,655 @@ impl Iterator for PatternRepeater {
 	}
 }
 
-#[derive(Debug, Clone, Copy)]
-struct IdRange {
-	start: u64,
-	end: u64,
+/// Which ids are invalid, ids are written in `base`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct InvalidRule {
+	shape: Shape,
+	base: u32,
 }
 
-impl IdRange {
-	fn invalid_ids_p1(&self) -> BTreeSet<u64> {
-		let start_digits = self.start.ilog10() + 1;
-		let end_digits = self.end.ilog10() + 1;
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Shape {
+	/// A pattern repeated between `min` and `max` times, both inclusive.
+	Repeats { min: u32, max: u32 },
+	/// Digits which read the same backwards.
+	Palindrome,
+}
 
-		// / 2 because we need to repeat at least twice
-		let min_pat_len = (start_digits) / 2;
-		let max_pat_len = (end_digits) / 2;
+impl InvalidRule {
+	pub const PART1: Self = Self::exactly(2);
+	pub const PART2: Self = Self::at_least(2);
 
-		let mut invalid_ids = BTreeSet::new();
+	pub const fn exactly(repeats: u32) -> Self {
+		Self::repeats(repeats, repeats)
+	}
 
-		for pat_len in min_pat_len..=max_pat_len {
-			if pat_len == 0 {
-				continue;
+	pub const fn at_least(repeats: u32) -> Self {
+		Self::repeats(repeats, u32::MAX)
+	}
+
+	pub const fn repeats(min: u32, max: u32) -> Self {
+		Self {
+			shape: Shape::Repeats { min, max },
+			base: 10,
+		}
+	}
+
+	pub const fn palindrome() -> Self {
+		Self {
+			shape: Shape::Palindrome,
+			base: 10,
+		}
+	}
+
+	/// ## Panics
+	/// If `base` is smaller than 2.
+	pub const fn in_base(self, base: u32) -> Self {
+		assert!(base >= 2, "base must be at least 2");
+		Self { base, ..self }
+	}
+
+	pub fn shape(self) -> Shape {
+		self.shape
+	}
+
+	pub fn base(self) -> u32 {
+		self.base
+	}
+
+	fn allows(self, repeats: u32) -> bool {
+		match self.shape {
+			Shape::Repeats { min, max } => (min..=max).contains(&repeats),
+			Shape::Palindrome => false,
+		}
+	}
+
+	/// Signed terms `(μ, d)` for the ids with `len` digits consisting of an
+	/// allowed number of repeats, each term stands for the ids with a
+	/// pattern of length `d`.
+	///
+	/// Every id has a shortest pattern, whose length divides the number of
+	/// digits, and it's invalid if that pattern can be repeated to a longer
+	/// pattern which is repeated an allowed number of times. The ids with a
+	/// shortest pattern of length `d` are all ids with a pattern of length
+	/// `d` minus the ones with an even shorter pattern dividing `d`, which
+	/// the Möbius inversion takes care of.
+	fn periods(self, len: u32) -> impl Iterator<Item = (i32, u32)> {
+		let divisors =
+			move || (1..=len).filter(move |d| len.is_multiple_of(*d));
+
+		let allowed = move |&period: &u32| {
+			divisors().any(|p| p.is_multiple_of(period) && self.allows(len / p))
+		};
+		divisors()
+			.filter(allowed)
+			.flat_map(move |period| {
+				divisors()
+					.filter(move |d| period.is_multiple_of(*d))
+					.map(move |d| (mobius(period / d), d))
+			})
+			.filter(|&(mu, _)| mu != 0)
+	}
+
+	/// Checks a single id digit by digit.
+	pub fn is_invalid<T: Int>(self, id: &T) -> bool {
+		let base = T::from_u64(self.base.into());
+		let mut digits = Vec::new();
+		let mut n = id.clone();
+		loop {
+			digits.push(n.rem(&base));
+			n = n.div(&base);
+			if n.is_zero() {
+				break;
 			}
+		}
 
-			let mut pat = 10u64.pow(pat_len - 1);
+		let len = digits.len();
+		match self.shape {
+			Shape::Palindrome => digits.iter().eq(digits.iter().rev()),
+			Shape::Repeats { .. } => (1..=len).any(|k| {
+				len.is_multiple_of(k)
+					&& self.allows(k as u32)
+					&& digits.chunks(len / k).all(|c| c == &digits[..len / k])
+			}),
+		}
+	}
+}
+
+/// The Möbius function, `0` if `n` has a squared prime factor, otherwise
+/// `1` or `-1` for an even or odd number of prime factors.
+fn mobius(mut n: u32) -> i32 {
+	let mut mu = 1;
+	let mut p = 2;
 
-			// in the first pat_len
-			// the pattern must be bigger then start
-			if pat_len == min_pat_len {
-				pat = self.start / 10u64.pow(pat_len);
+	while p * p <= n {
+		if n.is_multiple_of(p) {
+			n /= p;
+			if n.is_multiple_of(p) {
+				return 0;
 			}
+			mu = -mu;
+		}
+		p += 1;
+	}
+
+	if n > 1 { -mu } else { mu }
+}
 
-			let mut repeater = PatternRepeater::new(pat, 2, false);
+/// Count and sum of `min..=max`, halving before multiplying so only the sum
+/// itself has to fit.
+fn series<W: Int>(min: &W, max: &W) -> (W, W) {
+	let two = W::from_u64(2);
+	let count = max.sub(min).add(&W::from_u64(1));
+	let ends = min.add(max);
 
-			for pattern in &mut repeater {
-				if pattern > self.end {
-					break;
-				}
+	let sum = if count.rem(&two).is_zero() {
+		count.div(&two).mul(&ends)
+	} else {
+		ends.div(&two).mul(&count)
+	};
+	(count, sum)
+}
+
+/// The sum of digit `i` over all numbers in `0..n`.
+fn digit_sum<W: Int>(n: &W, i: u32, base: &W) -> W {
+	let one = W::from_u64(1);
+	let two = W::from_u64(2);
+	let place = base.pow(i);
+	let cycle = place.mul(base);
+
+	// every digit once per place in each full cycle
+	let full = n.div(&cycle);
+	let per_cycle = place.mul(base).mul(&base.sub(&one)).div(&two);
+
+	let rest = n.rem(&cycle);
+	let digit = rest.div(&place);
+	let below = if digit.is_zero() {
+		W::from_u64(0)
+	} else {
+		place.mul(&digit).mul(&digit.sub(&one)).div(&two)
+	};
+
+	full.mul(&per_cycle)
+		.add(&below)
+		.add(&digit.mul(&rest.rem(&place)))
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct IdRange<T = u64> {
+	pub start: T,
+	pub end: T,
+}
+
+impl IdRange {
+	pub fn new(start: u64, end: u64) -> Self {
+		Self { start, end }
+	}
+}
+
+/// Formatted like the input.
+impl<T: fmt::Display> fmt::Display for IdRange<T> {
+	fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+		write!(f, "{}-{}", self.start, self.end)
+	}
+}
+
+impl<T: Id> IdRange<T> {
+	/// The number of invalid ids, without building any of them.
+	pub fn count_invalid(&self, rule: InvalidRule) -> T::Wide {
+		self.invalid(rule).0
+	}
 
-				if pattern >= self.start {
-					invalid_ids.insert(pattern);
+	/// The sum of all invalid ids, without building any of them.
+	pub fn sum_invalid(&self, rule: InvalidRule) -> T::Wide {
+		self.invalid(rule).1
+	}
+
+	/// Count and sum of the invalid ids.
+	fn invalid(&self, rule: InvalidRule) -> (T::Wide, T::Wide) {
+		let zero = T::Wide::from_u64(0);
+		let base = T::Wide::from_u64(rule.base.into());
+		let (start, end) = (self.start.widen(), self.end.widen());
+
+		// the terms with a negative sign are summed up separately
+		let mut plus = (zero.clone(), zero.clone());
+		let mut minus = (zero.clone(), zero);
+		if start > end {
+			return plus;
+		}
+
+		let mut add = |mu: i32, (count, sum): (T::Wide, T::Wide)| {
+			let total = if mu > 0 { &mut plus } else { &mut minus };
+			total.0 = total.0.add(&count);
+			total.1 = total.1.add(&sum);
+		};
+
+		for len in start.digits(&base)..=end.digits(&base) {
+			match rule.shape {
+				Shape::Repeats { .. } => {
+					for (mu, d) in rule.periods(len) {
+						add(mu, periodic(len, d, &base, &start, &end));
+					}
+				}
+				Shape::Palindrome => {
+					add(1, palindromes(len, &base, &start, &end))
 				}
 			}
 		}
 
-		invalid_ids
+		(plus.0.sub(&minus.0), plus.1.sub(&minus.1))
 	}
 
-	fn invalid_ids_p2(&self) -> BTreeSet<u64> {
-		let start_digits = self.start.ilog10() + 1;
-		let end_digits = self.end.ilog10() + 1;
+	pub fn invalid_ids_p1(&self) -> BTreeSet<T> {
+		let mut invalid_ids = BTreeSet::new();
 
-		// / 2 because we need to repeat at least twice
-		let min_pat_len = (start_digits) / 2;
-		let max_pat_len = (end_digits) / 2;
+		for len in self.digit_lengths() {
+			if len.is_multiple_of(2) {
+				self.collect(len, len / 2, &mut invalid_ids);
+			}
+		}
+
+		invalid_ids
+	}
 
+	pub fn invalid_ids_p2(&self) -> BTreeSet<T> {
 		let mut invalid_ids = BTreeSet::new();
 
-		for pat_len in 1..=max_pat_len {
-			// if the pattern will never be able
-			// to fit in the range, skip it
-			if start_digits % pat_len != 0 && end_digits % pat_len != 0 {
-				// todo this might not work with big number ranges
-				continue;
+		// every length in between matters, not just the ones of start and
+		// end, e.g. 99999-1000001 contains 100100
+		for len in self.digit_lengths() {
+			// / 2 because we need to repeat at least twice
+			for pat_len in 1..=len / 2 {
+				if len.is_multiple_of(pat_len) {
+					self.collect(len, pat_len, &mut invalid_ids);
+				}
 			}
+		}
+
+		invalid_ids
+	}
+
+	fn digit_lengths(&self) -> std::ops::RangeInclusive<u32> {
+		let ten = T::from_u64(10);
+		self.start.digits(&ten)..=self.end.digits(&ten)
+	}
 
-			let mut pat = 10u64.pow(pat_len - 1);
+	/// Collects the ids with `len` digits in the range which repeat a
+	/// pattern of `pat_len` digits.
+	fn collect(&self, len: u32, pat_len: u32, ids: &mut BTreeSet<T>) {
+		let ten = T::from_u64(10);
+		let (start, end) = (self.start.widen(), self.end.widen());
 
-			// in the first pat_len
-			// the pattern must be bigger then start
-			if pat_len <= min_pat_len {
-				pat = self.start / 10u64.pow(start_digits - pat_len);
+		let mut pat = ten.pow(pat_len - 1);
+
+		// with as many digits as start
+		// the pattern must be bigger then its prefix
+		if len == self.start.digits(&ten) {
+			pat = self.start.div(&ten.pow(len - pat_len));
+		}
+
+		for pattern in PatternRepeater::new(pat, len / pat_len, false) {
+			if pattern > end {
+				break;
 			}
 
-			let mut repeater = PatternRepeater::new(
-				pat,
-				(start_digits / pat_len).max(2),
-				true,
-			);
+			if pattern >= start {
+				ids.extend(T::narrow(&pattern));
+			}
+		}
+	}
+}
 
-			for pattern in &mut repeater {
-				if pattern > self.end {
-					break;
-				}
+/// Count and sum of the ids with `len` digits in `start..=end` which consist
+/// of a pattern of length `period` repeated, as arithmetic series.
+fn periodic<W: Int>(
+	len: u32,
+	period: u32,
+	base: &W,
+	start: &W,
+	end: &W,
+) -> (W, W) {
+	let one = W::from_u64(1);
+	// e.g. 10101 for a pattern of length 2 and 6 digits
+	let multiplier = base.pow(len).sub(&one).div(&base.pow(period).sub(&one));
+
+	let mut min = start.div(&multiplier);
+	if !start.rem(&multiplier).is_zero() {
+		min = min.add(&one);
+	}
+	let min = min.max(base.pow(period - 1));
+	let max = end.div(&multiplier).min(base.pow(period).sub(&one));
+	if min > max {
+		return (W::from_u64(0), W::from_u64(0));
+	}
+
+	let (count, sum) = series(&min, &max);
+	(count, sum.mul(&multiplier))
+}
+
+/// Count and sum of the palindromes with `len` digits in `start..=end`.
+///
+/// A palindrome is determined by its first half `x` including the middle
+/// digit, the second half is the reversed first digits of `x`, so the sum
+/// of the second halves adds up the sums of each digit of `x`.
+fn palindromes<W: Int>(len: u32, base: &W, start: &W, end: &W) -> (W, W) {
+	let one = W::from_u64(1);
+	let zero = || W::from_u64(0);
+	let (half, mirrored) = (len.div_ceil(2), len / 2);
+	let middle = half - mirrored;
+	let shift = base.pow(mirrored);
+
+	let palindrome = |x: &W| {
+		let mut rest = x.div(&base.pow(middle));
+		let mut reversed = zero();
+		for _ in 0..mirrored {
+			reversed = reversed.mul(base).add(&rest.rem(base));
+			rest = rest.div(base);
+		}
+		x.mul(&shift).add(&reversed)
+	};
+
+	let mut min = start.div(&shift).max(base.pow(half - 1));
+	let mut max = end.div(&shift).min(base.pow(half).sub(&one));
+	if min > max {
+		return (zero(), zero());
+	}
+	if palindrome(&min) < *start {
+		min = min.add(&one);
+	}
+	if palindrome(&max) > *end {
+		max = max.sub(&one);
+	}
+	if min > max {
+		return (zero(), zero());
+	}
+
+	let (count, firsts) = series(&min, &max);
+	let mut sum = firsts.mul(&shift);
+	for j in 0..mirrored {
+		let upper = digit_sum(&max.add(&one), j + middle, base);
+		let lower = digit_sum(&min, j + middle, base);
+		sum = sum.add(&upper.sub(&lower).mul(&base.pow(mirrored - 1 - j)));
+	}
 
-				if pattern >= self.start {
-					invalid_ids.insert(pattern);
+	(count, sum)
+}
+
+fn parse_ranges_as<T: Int>(input: &str) -> Result<Vec<IdRange<T>>, ParseError> {
+	input
+		.trim()
+		.split(',')
+		.map(|r| {
+			let r = r.trim();
+			let (start, end) = r.split_once('-').ok_or_else(|| {
+				ParseError::at(input, r, "a range `start-end`")
+			})?;
+
+			let parse_id = |id: &str| match T::parse(id) {
+				Some(n) if !n.is_zero() => Ok(n),
+				// a number, just too large
+				None if !id.is_empty()
+					&& id.bytes().all(|b| b.is_ascii_digit()) =>
+				{
+					Err(ParseError::at(
+						input,
+						id,
+						format!("an id which fits into `{}`", T::NAME),
+					))
 				}
+				_ => Err(ParseError::at(input, id, "a positive id")),
+			};
+
+			let range = IdRange {
+				start: parse_id(start)?,
+				end: parse_id(end)?,
+			};
+			if range.end < range.start {
+				return Err(ParseError::at(
+					input,
+					end,
+					"an end not below the start",
+				));
 			}
+			Ok(range)
+		})
+		.collect()
+}
+
+pub fn parse_input(input: &str) -> Result<Vec<IdRange>, ParseError> {
+	parse_ranges_as(input)
+}
+
+/// Ranges in the smallest type all of their ids fit into.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Ranges {
+	U64(Vec<IdRange<u64>>),
+	U128(Vec<IdRange<u128>>),
+	Big(Vec<IdRange<BigUint>>),
+}
+
+impl Ranges {
+	pub fn count_invalid(&self, rule: InvalidRule) -> BigUint {
+		self.invalid(rule).0
+	}
+
+	pub fn sum_invalid(&self, rule: InvalidRule) -> BigUint {
+		self.invalid(rule).1
+	}
+
+	/// Sorted disjoint ranges containing each id once, see [`merge`].
+	pub fn merged(&self) -> Ranges {
+		match self {
+			Ranges::U64(ranges) => Ranges::U64(merge(ranges)),
+			Ranges::U128(ranges) => Ranges::U128(merge(ranges)),
+			Ranges::Big(ranges) => Ranges::Big(merge(ranges)),
 		}
+	}
 
-		invalid_ids
+	fn invalid(&self, rule: InvalidRule) -> (BigUint, BigUint) {
+		match self {
+			Ranges::U64(ranges) => total_invalid(ranges, rule),
+			Ranges::U128(ranges) => total_invalid(ranges, rule),
+			Ranges::Big(ranges) => total_invalid(ranges, rule),
+		}
 	}
 }
 
-fn parse_input(input: &str) -> impl Iterator<Item = IdRange> {
-	input.trim().split(',').map(|r| {
-		let mut parts = r.trim().split('-');
-		IdRange {
-			start: parts.next().unwrap().parse().unwrap(),
-			end: parts.next().unwrap().parse().unwrap(),
-		}
+/// Count and sum of the invalid ids of all ranges.
+fn total_invalid<T: Id>(
+	ranges: &[IdRange<T>],
+	rule: InvalidRule,
+) -> (BigUint, BigUint) {
+	let mut total = (BigUint::zero(), BigUint::zero());
+	for (count, sum) in ranges.iter().map(|r| r.invalid(rule)) {
+		total.0 += &count.to_big();
+		total.1 += &sum.to_big();
+	}
+	total
+}
+
+/// Parses the ranges into the smallest type which fits every id.
+pub fn parse_ranges(input: &str) -> Result<Ranges, ParseError> {
+	fn narrow<T>(
+		ranges: &[IdRange<BigUint>],
+		narrow: impl Fn(&BigUint) -> Option<T>,
+	) -> Option<Vec<IdRange<T>>> {
+		ranges
+			.iter()
+			.map(|r| {
+				Some(IdRange {
+					start: narrow(&r.start)?,
+					end: narrow(&r.end)?,
+				})
+			})
+			.collect()
+	}
+
+	let ranges = parse_ranges_as::<BigUint>(input)?;
+	Ok(if let Some(ranges) = narrow(&ranges, BigUint::to_u64) {
+		Ranges::U64(ranges)
+	} else if let Some(ranges) = narrow(&ranges, BigUint::to_u128) {
+		Ranges::U128(ranges)
+	} else {
+		Ranges::Big(ranges)
 	})
 }
 
-fn part1() -> u64 {
-	let input = parse_input(INPUT);
+/// How ids which are part of more than one range are counted.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Overlaps {
+	/// Once for every range, what the puzzle asks for.
+	#[default]
+	PerRange,
+	/// Once, by merging the ranges first.
+	Distinct,
+}
 
-	input.map(|r| r.invalid_ids_p1().iter().sum::<u64>()).sum()
+/// Two ranges of the input, by index, which share some ids.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Overlap<T = u64> {
+	pub first: usize,
+	pub second: usize,
+	pub shared: IdRange<T>,
 }
 
-fn part2() -> u64 {
-	let input = parse_input(INPUT);
+impl<T: fmt::Display> fmt::Display for Overlap<T> {
+	/// Ranges are numbered from 1 like in an editor.
+	fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+		write!(
+			f,
+			"ranges {} and {} share {}",
+			self.first + 1,
+			self.second + 1,
+			self.shared
+		)
+	}
+}
 
-	input.map(|r| r.invalid_ids_p2().iter().sum::<u64>()).sum()
+/// Every pair of ranges sharing ids, ordered by their indices.
+pub fn overlaps<T: Id>(ranges: &[IdRange<T>]) -> Vec<Overlap<T>> {
+	let mut order = (0..ranges.len()).collect::<Vec<_>>();
+	order.sort_unstable_by_key(|&i| (&ranges[i].start, &ranges[i].end));
+
+	// sweep by start, only ranges still open can overlap the next one
+	let mut open: Vec<usize> = Vec::new();
+	let mut overlaps = Vec::new();
+	for i in order {
+		let range = &ranges[i];
+		open.retain(|&j| ranges[j].end >= range.start);
+
+		for &j in &open {
+			overlaps.push(Overlap {
+				first: i.min(j),
+				second: i.max(j),
+				shared: IdRange {
+					start: range.start.clone(),
+					end: (&range.end).min(&ranges[j].end).clone(),
+				},
+			});
+		}
+		open.push(i);
+	}
+
+	overlaps.sort_unstable_by_key(|o| (o.first, o.second));
+	overlaps
+}
+
+/// Sorted disjoint ranges containing each id of `ranges` once, touching
+/// ranges are merged as well.
+pub fn merge<T: Id>(ranges: &[IdRange<T>]) -> Vec<IdRange<T>> {
+	let mut sorted = ranges.to_vec();
+	sorted.sort_unstable_by(|a, b| a.start.cmp(&b.start));
+
+	let one = T::Wide::from_u64(1);
+	let mut merged: Vec<IdRange<T>> = Vec::new();
+	for range in sorted {
+		match merged.last_mut() {
+			// widened so a range ending at the largest id still fits
+			Some(last) if range.start.widen() <= last.end.widen().add(&one) => {
+				if range.end > last.end {
+					last.end = range.end;
+				}
+			}
+			_ => merged.push(range),
+		}
+	}
+	merged
 }
 
-fn main() {
-	let p1 = part1();
-	println!("Part 1: {p1}");
-	assert_eq!(p1, 19219508902);
+/// The sum of the invalid ids, of any size.
+pub fn sum_invalid<T: Id>(
+	input: &[IdRange<T>],
+	rule: InvalidRule,
+	overlaps: Overlaps,
+) -> BigUint {
+	let merged;
+	let ranges = match overlaps {
+		Overlaps::PerRange => input,
+		Overlaps::Distinct => {
+			merged = merge(input);
+			&merged
+		}
+	};
 
-	let p2 = part2();
-	println!("Part 2: {p2}");
-	assert_eq!(p2, 27180728081);
+	total_invalid(ranges, rule).1
+}
+
+pub fn part1(ranges: Ranges) -> Result<u64, SolveError> {
+	answer(ranges.sum_invalid(InvalidRule::PART1))
+}
+
+pub fn part2(ranges: Ranges) -> Result<u64, SolveError> {
+	answer(ranges.sum_invalid(InvalidRule::PART2))
+}
+
+pub struct Day2;
+
+impl Solution for Day2 {
+	fn day(&self) -> u32 {
+		2
+	}
+
+	fn input(&self) -> &'static str {
+		INPUT
+	}
+
+	/// Ids of any size, as long as the answer fits into a `u64`.
+	fn part1(&self, input: &str) -> Result<u64, SolveError> {
+		part1(parse_ranges(input)?)
+	}
+
+	fn part2(&self, input: &str) -> Result<u64, SolveError> {
+		part2(parse_ranges(input)?)
+	}
+
+	fn bench(
+		&self,
+		input: &str,
+		runs: usize,
+		parts: &[Part],
+	) -> Result<DayTimings, ParseError> {
+		bench::phases(input, runs, parts, parse_ranges, part1, part2)
+	}
+}
+
+/// The sum of the invalid ids is too large to be an answer.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SumTooLarge {
+	pub sum: BigUint,
+}
+
+impl fmt::Display for SumTooLarge {
+	fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+		f.write_str("the sum of the invalid ids does not fit into `u64`")
+	}
+}
+
+impl Error for SumTooLarge {}
+
+fn answer(sum: BigUint) -> Result<u64, SolveError> {
+	sum.to_u64()
+		.ok_or_else(|| SolveError::unsolvable(SumTooLarge { sum }))
 }
 
 /*
@@ -206,7 +745,7 @@ fn test_invalid_ids_p1() {
 	macro_rules! check_ids {
 		($start:expr, $end:expr, [$($val:expr),*]) => {
 			{
-				let range = IdRange { start: $start, end: $end };
+				let range: IdRange = IdRange { start: $start, end: $end };
 				#[allow(unused_mut)]
 				let mut invalid_ids = range.invalid_ids_p1();
 				$(
@@ -249,7 +788,7 @@ fn test_invalid_ids_p2() {
 	macro_rules! check_ids {
 		($start:expr, $end:expr, [$($val:expr),*]) => {
 			{
-				let range = IdRange { start: $start, end: $end };
+				let range: IdRange = IdRange { start: $start, end: $end };
 				#[allow(unused_mut)]
 				let mut invalid_ids = range.invalid_ids_p2();
 				$(
@@ -273,3 +812,383 @@ fn test_invalid_ids_p2() {
 	check_ids!(824824821, 824824827, [824824824]);
 	check_ids!(2121212118, 2121212124, [2121212121]);
 }
+
+#[test]
+fn test_pattern_repeater() {
+	type Enumerate = fn(&IdRange) -> BTreeSet<u64>;
+	let cases: [(Enumerate, InvalidRule); 2] = [
+		(IdRange::invalid_ids_p1, InvalidRule::PART1),
+		(IdRange::invalid_ids_p2, InvalidRule::PART2),
+	];
+
+	// ranges around one or two digit length boundaries
+	let mut rng = crate::check::Rng::new(0x2021);
+	let mut ranges =
+		vec![IdRange::new(998, 1012), IdRange::new(99999, 1000001)];
+	for _ in 0..500 {
+		let boundary = 10u64.pow(1 + rng.below(11) as u32);
+		let below = 1 + rng.below(boundary.min(2000));
+		let above = match rng.below(100) {
+			0 => rng.below(boundary * 9).min(200_000),
+			_ => rng.below(2000),
+		};
+		ranges.push(IdRange::new(boundary - below, boundary + above));
+	}
+
+	let simpler = |range: &IdRange| {
+		let IdRange { start, end } = *range;
+		let mid = start + (end - start) / 2;
+		let mut variants = vec![
+			IdRange::new(start, mid),
+			IdRange::new(mid + 1, end),
+			IdRange::new(start + 1, end),
+			IdRange::new(start, end - 1),
+		];
+		variants.retain(|r| r.start <= r.end && r != range);
+		variants
+	};
+
+	for (enumerate, rule) in cases {
+		for &range in &ranges {
+			let fails = |r: &IdRange| enumerate(r) != oracle(*r, rule);
+			if fails(&range) {
+				let r = crate::check::shrink(range, simpler, fails);
+				panic!(
+					"{rule:?} disagrees on {range:?}, shrunk to {r:?}: {:?} != {:?}",
+					enumerate(&r),
+					oracle(r, rule)
+				);
+			}
+		}
+	}
+}
+
+/// The invalid ids found by writing out each id as a string, the most
+/// direct reading of the puzzle and independent of the rule's own checks.
+#[cfg(test)]
+fn oracle(range: IdRange, rule: InvalidRule) -> BTreeSet<u64> {
+	let base = rule.base();
+	let to_string = |mut id: u64| {
+		let mut digits = Vec::new();
+		loop {
+			digits.push(char::from_digit((id % base as u64) as u32, base));
+			id /= base as u64;
+			if id == 0 {
+				break;
+			}
+		}
+		digits
+			.into_iter()
+			.rev()
+			.collect::<Option<String>>()
+			.unwrap()
+	};
+
+	(range.start..=range.end)
+		.filter(|&id| {
+			let s = to_string(id);
+			let s = s.as_bytes();
+			match rule.shape() {
+				Shape::Palindrome => s.iter().eq(s.iter().rev()),
+				Shape::Repeats { min, max } => (1..=s.len()).any(|k| {
+					(min..=max).contains(&(k as u32))
+						&& s.len().is_multiple_of(k)
+						&& s.chunks(s.len() / k).all(|c| c == &s[..s.len() / k])
+				}),
+			}
+		})
+		.collect()
+}
+
+/// The count and sum of the ids found by [`oracle`].
+#[cfg(test)]
+fn oracle_total(range: IdRange, rule: InvalidRule) -> (u128, u128) {
+	let ids = oracle(range, rule);
+	(ids.len() as u128, ids.iter().map(|&id| id as u128).sum())
+}
+
+#[test]
+fn test_closed_form() {
+	let check = |range: IdRange| {
+		for rule in [InvalidRule::PART1, InvalidRule::PART2] {
+			assert_eq!(
+				(range.count_invalid(rule), range.sum_invalid(rule)),
+				oracle_total(range, rule),
+				"{range:?} {rule:?}"
+			);
+		}
+	};
+
+	check(IdRange::new(11, 22));
+	check(IdRange::new(95, 115));
+	check(IdRange::new(998, 1012));
+	check(IdRange::new(1, 100_000));
+	check(IdRange::new(2121212118, 2121212124));
+
+	let mut rng = crate::check::Rng::new(0xd2);
+	for _ in 0..200 {
+		let digits = 1 + rng.below(9) as u32;
+		let start = 1 + rng.below(10u64.pow(digits));
+		check(IdRange::new(start, start + rng.below(10_000)));
+	}
+
+	// 1 to 99 once, then all of them twice
+	let range = IdRange::new(1, 9999);
+	assert_eq!(range.count_invalid(InvalidRule::PART1), 99);
+	assert_eq!(range.count_invalid(InvalidRule::exactly(4)), 9);
+	assert_eq!(range.count_invalid(InvalidRule::at_least(1)), 9999);
+	assert_eq!(range.count_invalid(InvalidRule::at_least(3)), 9 + 9);
+
+	// too large to build every id
+	let range = IdRange::new(1, u64::MAX);
+	assert_eq!(
+		range.count_invalid(InvalidRule::PART1),
+		(1..=9).map(|p| 9 * 10u128.pow(p - 1)).sum::<u128>()
+			+ (1844674407 - 999999999)
+	);
+	assert_eq!(
+		range.sum_invalid(InvalidRule::at_least(1)),
+		u64::MAX as u128 * (u64::MAX as u128 + 1) / 2
+	);
+}
+
+#[test]
+fn test_rules() {
+	let rules = [
+		InvalidRule::exactly(3),
+		InvalidRule::repeats(2, 3),
+		InvalidRule::palindrome(),
+		InvalidRule::PART1.in_base(2),
+		InvalidRule::PART2.in_base(2),
+		InvalidRule::palindrome().in_base(2),
+		InvalidRule::PART2.in_base(16),
+		InvalidRule::palindrome().in_base(16),
+		InvalidRule::palindrome().in_base(7),
+	];
+
+	let mut rng = crate::check::Rng::new(0x2020);
+	for _ in 0..300 {
+		let digits = 1 + rng.below(12) as u32;
+		let start = 1 + rng.below(10u64.pow(digits));
+		let range = IdRange::new(start, start + rng.below(5000));
+		let rule = rules[rng.below(rules.len() as u64) as usize];
+		assert_eq!(
+			(range.count_invalid(rule), range.sum_invalid(rule)),
+			oracle_total(range, rule),
+			"{range:?} {rule:?}"
+		);
+	}
+
+	let range = IdRange::new(1, 9999);
+	let palindrome = InvalidRule::palindrome();
+	assert_eq!(range.count_invalid(palindrome), 9 + 9 + 90 + 90);
+	assert_eq!(range.count_invalid(InvalidRule::repeats(3, 4)), 18);
+	assert!(palindrome.is_invalid(&12321u64));
+	assert!(!palindrome.is_invalid(&12312u64));
+	assert!(InvalidRule::PART1.in_base(16).is_invalid(&0xabab_u64));
+	assert!(InvalidRule::PART1.in_base(2).is_invalid(&0b1010_u64));
+	assert!(!InvalidRule::PART1.in_base(2).is_invalid(&0b1011_u64));
+
+	// 1 to 2^32 - 1 repeated twice in binary, with their leading one
+	let range = IdRange::new(1, u64::MAX);
+	let binary = InvalidRule::PART1.in_base(2);
+	assert_eq!(range.count_invalid(binary), (1 << 32) - 1);
+	assert_eq!(
+		range.count_invalid(InvalidRule::palindrome().in_base(2)),
+		2 * ((1 << 32) - 1)
+	);
+}
+
+#[test]
+fn test_wide_ids() {
+	let input = "11-22,95-115,998-1012";
+	let ranges = parse_ranges(input).unwrap();
+	assert!(matches!(ranges, Ranges::U64(_)));
+	assert_eq!(
+		ranges.sum_invalid(InvalidRule::PART2),
+		sum_invalid(
+			&parse_input(input).unwrap(),
+			InvalidRule::PART2,
+			Overlaps::PerRange
+		)
+	);
+
+	// the same ranges in every width agree
+	let mut rng = crate::check::Rng::new(0x128);
+	for _ in 0..50 {
+		let start = 1 + rng.below(1 << 40);
+		let range = IdRange::new(start, start + rng.below(1 << 30));
+		let wide = IdRange {
+			start: range.start as u128,
+			end: range.end as u128,
+		};
+		let big = IdRange {
+			start: range.start.to_big(),
+			end: range.end.to_big(),
+		};
+		for rule in [InvalidRule::PART2, InvalidRule::palindrome()] {
+			let sum = range.sum_invalid(rule);
+			assert_eq!(wide.sum_invalid(rule), BigUint::from(sum));
+			assert_eq!(big.sum_invalid(rule), BigUint::from(sum));
+		}
+	}
+
+	assert_eq!(
+		parse_ranges("18446744073709551615-18446744073709551616"),
+		Ok(Ranges::U128(vec![IdRange {
+			start: u64::MAX as u128,
+			end: 1 << 64
+		}]))
+	);
+	assert_eq!(
+		parse_input("1-18446744073709551616").unwrap_err(),
+		ParseError::new(
+			1,
+			3,
+			"18446744073709551616",
+			"an id which fits into `u64`"
+		)
+	);
+
+	let ranges = parse_ranges(
+		"121212121212121212121210-121212121212121212121219,\
+		1000000000000000000000000000000000000000-\
+		1000000000000000000000000000000000000002",
+	)
+	.unwrap();
+	assert!(matches!(ranges, Ranges::Big(_)));
+	assert_eq!(
+		ranges.sum_invalid(InvalidRule::PART2),
+		BigUint::parse("121212121212121212121212").unwrap()
+	);
+	assert_eq!(
+		ranges.count_invalid(InvalidRule::palindrome()),
+		BigUint::from(1u64)
+	);
+
+	let range = IdRange {
+		start: 1,
+		end: u128::MAX,
+	};
+	assert_eq!(
+		range.count_invalid(InvalidRule::PART1.in_base(2)),
+		BigUint::from(u64::MAX)
+	);
+	assert_eq!(
+		range.sum_invalid(InvalidRule::at_least(1)),
+		&BigUint::from(u128::MAX) * &BigUint::from(1u128 << 127)
+	);
+}
+
+#[test]
+fn test_overlaps() {
+	let ranges = parse_input("11-22,15-30,95-115,20-25,31-40").unwrap();
+
+	let found = overlaps(&ranges);
+	assert_eq!(
+		found.iter().map(|o| o.to_string()).collect::<Vec<_>>(),
+		[
+			"ranges 1 and 2 share 15-22",
+			"ranges 1 and 4 share 20-22",
+			"ranges 2 and 4 share 20-25",
+		]
+	);
+	assert_eq!(
+		merge(&ranges),
+		[IdRange::new(11, 40), IdRange::new(95, 115)]
+	);
+
+	// 22 is in three ranges
+	let rule = InvalidRule::PART1;
+	let sum = |ranges: &[IdRange], overlaps| {
+		sum_invalid(ranges, rule, overlaps).to_u64().unwrap()
+	};
+	assert_eq!(sum(&ranges, Overlaps::PerRange), 209);
+	assert_eq!(sum(&ranges, Overlaps::Distinct), 165);
+
+	let ranges = [IdRange::new(1, u64::MAX), IdRange::new(u64::MAX, u64::MAX)];
+	assert_eq!(merge(&ranges), [IdRange::new(1, u64::MAX)]);
+	assert_eq!(
+		overlaps(&ranges)[0].shared,
+		IdRange::new(u64::MAX, u64::MAX)
+	);
+
+	let ranges = parse_input(INPUT).unwrap();
+	assert!(overlaps(&ranges).is_empty());
+	assert_eq!(
+		sum_invalid(&ranges, InvalidRule::PART2, Overlaps::Distinct),
+		BigUint::from(part2(Ranges::U64(ranges)).unwrap())
+	);
+
+	// wider than a u128, 2^128 is in both
+	let wide = parse_ranges(
+		"340282366920938463463374607431768211455-340282366920938463463374607431768211457,\
+		 340282366920938463463374607431768211456-340282366920938463463374607431768211458",
+	)
+	.unwrap();
+	let Ranges::Big(big) = &wide else {
+		panic!("{wide:?} should be parsed as big ids");
+	};
+	let start = BigUint::from(u128::MAX);
+	let two = BigUint::from(2u64);
+	assert_eq!(
+		overlaps(big),
+		[Overlap {
+			first: 0,
+			second: 1,
+			shared: IdRange {
+				start: &start + &BigUint::from(1u64),
+				end: &start + &two,
+			},
+		}]
+	);
+	assert_eq!(
+		wide.merged(),
+		Ranges::Big(vec![IdRange {
+			start: start.clone(),
+			end: &start + &BigUint::from(3u64),
+		}])
+	);
+	let rule = InvalidRule::at_least(1);
+	assert_eq!(
+		wide.merged().sum_invalid(rule),
+		sum_invalid(big, rule, Overlaps::Distinct)
+	);
+	assert_eq!(
+		sum_invalid(big, rule, Overlaps::Distinct),
+		&(&start * &BigUint::from(4u64)) + &BigUint::from(6u64)
+	);
+}
+
+#[test]
+fn test_solution() {
+	assert_eq!(Day2.part2("11-22,95-115").unwrap(), 33 + 99 + 111);
+	// wider than a u64 but without invalid ids
+	assert_eq!(
+		Day2.part1("18446744073709551616-18446744073709551620")
+			.unwrap(),
+		0
+	);
+	assert_eq!(
+		Day2.part1("1-99999999999999999999")
+			.unwrap_err()
+			.to_string(),
+		"no answer: the sum of the invalid ids does not fit into `u64`"
+	);
+}
+
+#[test]
+fn test_parse_error() {
+	assert_eq!(
+		parse_input("11-22,95115").unwrap_err(),
+		ParseError::new(1, 7, "95115", "a range `start-end`")
+	);
+	assert_eq!(
+		parse_input("11-22,\n95-abc").unwrap_err(),
+		ParseError::new(2, 4, "abc", "a positive id")
+	);
+	assert_eq!(
+		parse_input("11-22,95-9").unwrap_err(),
+		ParseError::new(1, 10, "9", "an end not below the start")
+	);
+}
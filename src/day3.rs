@@ -1,6 +1,13 @@
-use std::slice;
+use std::{cmp::Reverse, error::Error, fmt, slice};
 
-const INPUT: &str = include_str!("../inputs/day3.txt");
+use crate::{
+	bench::{self, DayTimings},
+	bigint::BigUint,
+	parse::ParseError,
+	solution::{Part, Solution, SolveError},
+};
+
+pub const INPUT: &str = include_str!("../inputs/day3.txt");
 
 struct DigitIter<'a> {
 	numbers: slice::Iter<'a, u8>,
@@ -25,19 +32,23 @@ impl Iterator for DigitIter<'_> {
 	}
 }
 
-struct BatteryBank<'a> {
+#[derive(Clone)]
+pub struct BatteryBank<'a> {
 	// numbers in bytes not actual number
 	numbers: &'a [u8],
 }
 
 impl<'a> BatteryBank<'a> {
-	fn new(numbers: &'a str) -> BatteryBank<'a> {
+	pub fn new(numbers: &'a str) -> BatteryBank<'a> {
 		BatteryBank {
 			numbers: numbers.as_bytes(),
 		}
 	}
 
-	fn largest_jolt<const S: usize>(&self) -> u64 {
+	/// The largest joltage of `S` batteries, faster than
+	/// [`largest_jolt`](Self::largest_jolt) when `S` is known at compile
+	/// time.
+	pub fn largest_jolt_const<const S: usize>(&self) -> u64 {
 		let mut iter = DigitIter::new(self.numbers);
 
 		let mut arr = [0u8; S];
@@ -63,32 +74,329 @@ impl<'a> BatteryBank<'a> {
 
 		arr.iter().fold(0, |acc, d| acc * 10 + *d as u64)
 	}
+
+	/// The `k` batteries giving the largest joltage, like
+	/// [`largest_jolt_const`](Self::largest_jolt_const) but with `k` chosen
+	/// at runtime.
+	///
+	/// Keeps the chosen batteries on a stack, a larger digit replaces the
+	/// smaller ones before it as long as enough batteries are left to still
+	/// pick `k` of them.
+	///
+	/// ## Panics
+	/// If the bank has less than `k` batteries.
+	pub fn largest_jolt(&self, k: usize) -> Selection<'a> {
+		let n = self.numbers.len();
+		assert!(k <= n, "can't turn on {k} of {n} batteries");
+
+		let mut drops = n - k;
+		let mut stack: Vec<usize> = Vec::with_capacity(n);
+		for (i, d) in self.numbers.iter().enumerate() {
+			while drops > 0
+				&& stack.last().is_some_and(|&j| self.numbers[j] < *d)
+			{
+				stack.pop();
+				drops -= 1;
+			}
+			stack.push(i);
+		}
+		stack.truncate(k);
+
+		Selection {
+			numbers: self.numbers,
+			positions: stack,
+		}
+	}
+
+	/// The `k` batteries giving the smallest joltage without a leading
+	/// zero, `None` if there is no such joltage.
+	pub fn smallest(&self, k: usize) -> Option<Selection<'a>> {
+		self.greedy(k, 1, |j, window| {
+			let allowed = window
+				.iter()
+				.enumerate()
+				.filter(|&(_, &d)| j > 0 || d != b'0');
+			// the first one of the smallest digits
+			allowed.min_by_key(|&(_, &d)| d).map(|(i, _)| i)
+		})
+	}
+
+	/// The `k` batteries giving the largest joltage where the positions of
+	/// two batteries differ by at least `gap`, `None` if they don't fit.
+	///
+	/// ## Panics
+	/// If `gap` is zero.
+	pub fn largest_spaced(
+		&self,
+		k: usize,
+		gap: usize,
+	) -> Option<Selection<'a>> {
+		assert!(gap > 0, "gap must be at least 1");
+		self.greedy(k, gap, |_, window| {
+			// the first one of the largest digits
+			let max = window.iter().max()?;
+			window.iter().position(|d| d == max)
+		})
+	}
+
+	/// Picks `k` batteries at least `gap` apart from left to right.
+	///
+	/// `choose` gets the index of the pick and the batteries it can choose
+	/// from while leaving room for the rest. Choosing the first of equally
+	/// good batteries is optimal, it leaves the most options for the rest.
+	fn greedy(
+		&self,
+		k: usize,
+		gap: usize,
+		choose: impl Fn(usize, &[u8]) -> Option<usize>,
+	) -> Option<Selection<'a>> {
+		let n = self.numbers.len();
+		let mut positions = Vec::with_capacity(k);
+		let mut start = 0;
+
+		for j in 0..k {
+			let last = n.checked_sub(1 + (k - 1 - j) * gap)?;
+			let window = self.numbers.get(start..=last)?;
+			let pick = start + choose(j, window)?;
+			positions.push(pick);
+			start = pick + gap;
+		}
+
+		Some(Selection {
+			numbers: self.numbers,
+			positions,
+		})
+	}
+
+	/// The `rank`th largest of all different joltages of `k` batteries,
+	/// starting at 1 for the largest, `None` if there are fewer.
+	///
+	/// Counts the different joltages of each length in each suffix, a
+	/// joltage is counted for the first occurrence of its first digit.
+	/// Then the digits are picked from the largest down, skipping over all
+	/// joltages starting with a larger digit.
+	pub fn kth_largest(&self, k: usize, rank: u64) -> Option<Selection<'a>> {
+		let n = self.numbers.len();
+
+		// next[i][d]: first position at or after i with digit d
+		let mut next = vec![[None; 10]; n + 1];
+		for i in (0..n).rev() {
+			next[i] = next[i + 1];
+			next[i][(self.numbers[i] - b'0') as usize] = Some(i);
+		}
+
+		// count[i][l]: different joltages of length l in the suffix at i
+		let mut count = vec![vec![0u64; k + 1]; n + 2];
+		for i in (0..=n).rev() {
+			count[i][0] = 1;
+			for l in 1..=k {
+				count[i][l] = next[i].iter().flatten().fold(0u64, |sum, &p| {
+					sum.saturating_add(count[p + 1][l - 1])
+				});
+			}
+		}
+
+		let mut rank = rank.checked_sub(1)?;
+		let mut positions = Vec::with_capacity(k);
+		let mut start = 0;
+		for l in (1..=k).rev() {
+			let pick = next[start].iter().rev().flatten().find(|&&p| {
+				let joltages = count[p + 1][l - 1];
+				if rank < joltages {
+					return true;
+				}
+				rank -= joltages;
+				false
+			})?;
+			positions.push(*pick);
+			start = pick + 1;
+		}
+
+		// only the empty joltage is left when nothing gets picked
+		(rank == 0).then_some(Selection {
+			numbers: self.numbers,
+			positions,
+		})
+	}
+
+	/// The `k` batteries with the largest sum of their digits, the first
+	/// ones of equal digits.
+	///
+	/// ## Panics
+	/// If the bank has less than `k` batteries.
+	pub fn largest_digit_sum(&self, k: usize) -> Selection<'a> {
+		let n = self.numbers.len();
+		assert!(k <= n, "can't turn on {k} of {n} batteries");
+
+		let mut positions = (0..n).collect::<Vec<_>>();
+		positions.sort_by_key(|&i| Reverse(self.numbers[i]));
+		positions.truncate(k);
+		positions.sort_unstable();
+
+		Selection {
+			numbers: self.numbers,
+			positions,
+		}
+	}
+}
+
+/// Batteries turned on in a bank.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Selection<'a> {
+	numbers: &'a [u8],
+	positions: Vec<usize>,
+}
+
+impl Selection<'_> {
+	/// Indices into the bank, ascending.
+	pub fn positions(&self) -> &[usize] {
+		&self.positions
+	}
+
+	/// The joltage, `None` if it does not fit into a `u64`.
+	pub fn value(&self) -> Option<u64> {
+		DigitIter::new(&self.bytes())
+			.try_fold(0u64, |acc, d| acc.checked_mul(10)?.checked_add(d as u64))
+	}
+
+	pub fn digit_sum(&self) -> u32 {
+		DigitIter::new(&self.bytes()).map(u32::from).sum()
+	}
+
+	/// The joltage of any size.
+	pub fn to_big(&self) -> BigUint {
+		let ten = BigUint::from(10u64);
+		DigitIter::new(&self.bytes()).fold(BigUint::zero(), |n, d| {
+			&(&n * &ten) + &BigUint::from(d as u64)
+		})
+	}
+
+	/// The digits of the batteries which stay off, in order.
+	pub fn skipped(&self) -> String {
+		let mut on = self.positions.iter().peekable();
+		self.numbers
+			.iter()
+			.enumerate()
+			.filter(|(i, _)| on.next_if_eq(&i).is_none())
+			.map(|(_, &d)| char::from(d))
+			.collect()
+	}
+
+	/// The bank with a `^` under every battery turned on.
+	pub fn render(&self) -> String {
+		let mut marks = vec![b' '; self.numbers.len()];
+		for &i in &self.positions {
+			marks[i] = b'^';
+		}
+
+		format!(
+			"{}\n{}",
+			String::from_utf8_lossy(self.numbers),
+			String::from_utf8_lossy(&marks).trim_end()
+		)
+	}
+
+	fn bytes(&self) -> Vec<u8> {
+		self.positions.iter().map(|&i| self.numbers[i]).collect()
+	}
+}
+
+/// The digits as written in the bank, so leading zeros are kept.
+impl fmt::Display for Selection<'_> {
+	fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+		f.write_str(&String::from_utf8_lossy(&self.bytes()))
+	}
 }
 
-fn parse_input<'a>(input: &'a str) -> impl Iterator<Item = BatteryBank<'a>> {
-	input.trim().lines().map(|l| BatteryBank::new(l.trim()))
+pub fn parse_input(input: &str) -> Result<Vec<BatteryBank<'_>>, ParseError> {
+	input
+		.trim()
+		.lines()
+		.map(|l| {
+			let l = l.trim();
+
+			match l.find(|c: char| !c.is_ascii_digit()) {
+				Some(i) => Err(ParseError::at_char(input, &l[i..], "a digit")),
+				None => Ok(BatteryBank::new(l)),
+			}
+		})
+		.collect()
+}
+
+/// A bank with fewer batteries than have to be turned on.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct TooFewBatteries {
+	/// 1-based index of the bank
+	pub bank: usize,
+	pub batteries: usize,
+	pub needed: usize,
 }
 
-fn part1() -> u64 {
-	let banks = parse_input(INPUT);
+impl fmt::Display for TooFewBatteries {
+	fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+		write!(
+			f,
+			"bank {} has {} batteries but {} have to be turned on",
+			self.bank, self.batteries, self.needed
+		)
+	}
+}
 
-	banks.map(|b| b.largest_jolt::<2>()).sum()
+impl Error for TooFewBatteries {}
+
+/// The sum of the largest joltages of `S` batteries in every bank.
+fn total_jolt<const S: usize>(
+	banks: &[BatteryBank<'_>],
+) -> Result<u64, TooFewBatteries> {
+	banks
+		.iter()
+		.enumerate()
+		.map(|(i, bank)| match bank.numbers.len() {
+			n if n < S => Err(TooFewBatteries {
+				bank: i + 1,
+				batteries: n,
+				needed: S,
+			}),
+			_ => Ok(bank.largest_jolt_const::<S>()),
+		})
+		.sum()
 }
 
-fn part2() -> u64 {
-	let banks = parse_input(INPUT);
+pub fn part1(banks: Vec<BatteryBank<'_>>) -> Result<u64, TooFewBatteries> {
+	total_jolt::<2>(&banks)
+}
 
-	banks.map(|b| b.largest_jolt::<12>()).sum()
+pub fn part2(banks: Vec<BatteryBank<'_>>) -> Result<u64, TooFewBatteries> {
+	total_jolt::<12>(&banks)
 }
 
-fn main() {
-	let p1 = part1();
-	println!("Part 1: {p1}");
-	assert_eq!(p1, 17321);
+pub struct Day3;
 
-	let p2 = part2();
-	println!("Part 2: {p2}");
-	assert_eq!(p2, 171989894144198);
+impl Solution for Day3 {
+	fn day(&self) -> u32 {
+		3
+	}
+
+	fn input(&self) -> &'static str {
+		INPUT
+	}
+
+	fn part1(&self, input: &str) -> Result<u64, SolveError> {
+		part1(parse_input(input)?).map_err(SolveError::unsolvable)
+	}
+
+	fn part2(&self, input: &str) -> Result<u64, SolveError> {
+		part2(parse_input(input)?).map_err(SolveError::unsolvable)
+	}
+
+	fn bench(
+		&self,
+		input: &str,
+		runs: usize,
+		parts: &[Part],
+	) -> Result<DayTimings, ParseError> {
+		bench::phases(input, runs, parts, parse_input, part1, part2)
+	}
 }
 
 /*
@@ -99,10 +407,22 @@ fn main() {
 */
 #[test]
 fn test_p1() {
-	assert_eq!(BatteryBank::new("987654321111111").largest_jolt::<2>(), 98);
-	assert_eq!(BatteryBank::new("811111111111119").largest_jolt::<2>(), 89);
-	assert_eq!(BatteryBank::new("234234234234278").largest_jolt::<2>(), 78);
-	assert_eq!(BatteryBank::new("818181911112111").largest_jolt::<2>(), 92);
+	assert_eq!(
+		BatteryBank::new("987654321111111").largest_jolt_const::<2>(),
+		98
+	);
+	assert_eq!(
+		BatteryBank::new("811111111111119").largest_jolt_const::<2>(),
+		89
+	);
+	assert_eq!(
+		BatteryBank::new("234234234234278").largest_jolt_const::<2>(),
+		78
+	);
+	assert_eq!(
+		BatteryBank::new("818181911112111").largest_jolt_const::<2>(),
+		92
+	);
 }
 
 /*
@@ -114,19 +434,207 @@ fn test_p1() {
 #[test]
 fn test_p2() {
 	assert_eq!(
-		BatteryBank::new("987654321111111").largest_jolt::<12>(),
+		BatteryBank::new("987654321111111").largest_jolt_const::<12>(),
 		987654321111
 	);
 	assert_eq!(
-		BatteryBank::new("811111111111119").largest_jolt::<12>(),
+		BatteryBank::new("811111111111119").largest_jolt_const::<12>(),
 		811111111119
 	);
 	assert_eq!(
-		BatteryBank::new("234234234234278").largest_jolt::<12>(),
+		BatteryBank::new("234234234234278").largest_jolt_const::<12>(),
 		434234234278
 	);
 	assert_eq!(
-		BatteryBank::new("818181911112111").largest_jolt::<12>(),
+		BatteryBank::new("818181911112111").largest_jolt_const::<12>(),
 		888911112111
 	);
 }
+
+#[test]
+fn test_largest_jolt() {
+	let bank = BatteryBank::new("818181911112111");
+	let twelve = bank.largest_jolt(12);
+	assert_eq!(twelve.value(), Some(888911112111));
+	assert_eq!(
+		twelve.positions(),
+		[0, 2, 4, 6, 7, 8, 9, 10, 11, 12, 13, 14]
+	);
+	assert_eq!(bank.largest_jolt(2).positions(), [6, 11]);
+	assert_eq!(bank.largest_jolt(0).value(), Some(0));
+	assert_eq!(bank.largest_jolt(15).value(), Some(818181911112111));
+
+	let long = "9".repeat(20);
+	assert_eq!(BatteryBank::new(&long).largest_jolt(20).value(), None);
+	assert_eq!(
+		BatteryBank::new(&long).largest_jolt(19).value(),
+		Some(9_999_999_999_999_999_999)
+	);
+
+	let mut rng = crate::check::Rng::new(0x3);
+	for _ in 0..500 {
+		let len = 12 + rng.below(30) as usize;
+		let line = (0..len)
+			.map(|_| char::from(b'1' + rng.below(9) as u8))
+			.collect::<String>();
+		let bank = BatteryBank::new(&line);
+
+		assert_eq!(
+			bank.largest_jolt(2).value(),
+			Some(bank.largest_jolt_const::<2>())
+		);
+		assert_eq!(
+			bank.largest_jolt(12).value(),
+			Some(bank.largest_jolt_const::<12>())
+		);
+		let k = rng.below(len as u64 + 1) as usize;
+		assert!(bank.largest_jolt(k).positions().is_sorted());
+		assert_eq!(bank.largest_jolt(k).positions().len(), k);
+	}
+}
+
+#[test]
+fn test_selection() {
+	let bank = BatteryBank::new("987654321111111");
+	let jolt = bank.largest_jolt(12);
+	assert_eq!(jolt.to_string(), "987654321111");
+	// everything except some 1s at the end
+	assert_eq!(jolt.skipped(), "111");
+	assert_eq!(jolt.render(), "987654321111111\n^^^^^^^^^^^^");
+
+	// everything except a 2, a 3 and another 2 near the start
+	let jolt = BatteryBank::new("234234234234278").largest_jolt(12);
+	assert_eq!(jolt.skipped(), "232");
+	assert_eq!(jolt.render(), "234234234234278\n  ^ ^^^^^^^^^^^");
+
+	let jolt = BatteryBank::new("818181911112111").largest_jolt(2);
+	assert_eq!(jolt.render(), "818181911112111\n      ^    ^");
+
+	let line = "1234567890".repeat(4);
+	let jolt = BatteryBank::new(&line).largest_jolt(25);
+	assert_eq!(jolt.value(), None);
+	assert_eq!(jolt.to_string(), "9789012345678901234567890");
+	assert_eq!(jolt.to_big().to_string(), jolt.to_string());
+	assert_eq!(
+		BatteryBank::new("").largest_jolt(0).to_big(),
+		BigUint::zero()
+	);
+
+	let jolt = BatteryBank::new("0901").largest_jolt(3);
+	assert_eq!(jolt.to_string(), "901");
+	let jolt = BatteryBank::new("0012").largest_jolt(3);
+	assert_eq!(jolt.to_string(), "012");
+	assert_eq!(jolt.value(), Some(12));
+}
+
+#[test]
+fn test_objectives() {
+	let bank = BatteryBank::new("818181911112111");
+	assert_eq!(bank.smallest(2).unwrap().to_string(), "11");
+	assert_eq!(bank.kth_largest(2, 1), Some(bank.largest_jolt(2)));
+	assert_eq!(bank.kth_largest(2, 2).unwrap().to_string(), "91");
+	assert_eq!(bank.kth_largest(2, 3).unwrap().to_string(), "89");
+	assert_eq!(bank.kth_largest(1, 3).unwrap().to_string(), "2");
+	assert_eq!(bank.kth_largest(1, 5), None);
+	assert_eq!(bank.kth_largest(0, 1), Some(bank.largest_jolt(0)));
+	assert_eq!(bank.kth_largest(0, 2), None);
+	assert_eq!(bank.largest_spaced(2, 6).unwrap().to_string(), "91");
+	assert_eq!(bank.largest_spaced(3, 7).unwrap().to_string(), "811");
+	assert_eq!(bank.largest_spaced(3, 8), None);
+	assert_eq!(bank.largest_digit_sum(3).to_string(), "889");
+	assert_eq!(bank.largest_digit_sum(3).digit_sum(), 25);
+
+	let zeros = BatteryBank::new("000");
+	assert_eq!(zeros.smallest(1), None);
+	assert_eq!(zeros.kth_largest(2, 1).unwrap().to_string(), "00");
+	assert_eq!(zeros.kth_largest(2, 2), None);
+	assert_eq!(
+		BatteryBank::new("3021").smallest(3).unwrap().to_string(),
+		"301"
+	);
+
+	// every selection of small banks
+	let mut rng = crate::check::Rng::new(0x25);
+	for _ in 0..300 {
+		let len = 1 + rng.below(9) as usize;
+		let line = (0..len)
+			.map(|_| char::from(b'0' + rng.below(4) as u8 * 3))
+			.collect::<String>();
+		let bank = BatteryBank::new(&line);
+		let k = 1 + rng.below(len as u64) as usize;
+
+		let mut all = (0u32..1 << len)
+			.filter(|mask| mask.count_ones() as usize == k)
+			.map(|mask| {
+				(0..len).filter(|i| mask & 1 << i != 0).collect::<Vec<_>>()
+			})
+			.collect::<Vec<_>>();
+		let jolt = |positions: &[usize]| {
+			positions.iter().map(|&i| &line[i..=i]).collect::<String>()
+		};
+
+		let smallest =
+			all.iter().map(|p| jolt(p)).filter(|j| !j.starts_with('0'));
+		assert_eq!(
+			bank.smallest(k).map(|s| s.to_string()),
+			smallest.min(),
+			"{line} {k}"
+		);
+
+		let mut distinct = all.iter().map(|p| jolt(p)).collect::<Vec<_>>();
+		distinct.sort_unstable_by(|a, b| b.cmp(a));
+		distinct.dedup();
+		for (rank, joltage) in distinct.iter().enumerate() {
+			let selection = bank.kth_largest(k, rank as u64 + 1).unwrap();
+			assert_eq!(&selection.to_string(), joltage, "{line} {k} {rank}");
+		}
+		assert_eq!(bank.kth_largest(k, distinct.len() as u64 + 1), None);
+
+		let sums = all.iter().map(|p| {
+			p.iter()
+				.map(|&i| u32::from(line.as_bytes()[i] - b'0'))
+				.sum()
+		});
+		assert_eq!(Some(bank.largest_digit_sum(k).digit_sum()), sums.max());
+
+		let gap = 1 + rng.below(3) as usize;
+		let spaced = bank.largest_spaced(k, gap);
+		if let Some(spaced) = &spaced {
+			let gaps =
+				spaced.positions().windows(2).all(|w| w[1] - w[0] >= gap);
+			assert!(gaps, "{line} {k} {gap}");
+		}
+		all.retain(|p| p.windows(2).all(|w| w[1] - w[0] >= gap));
+		assert_eq!(
+			spaced.map(|s| s.to_string()),
+			all.iter().map(|p| jolt(p)).max()
+		);
+	}
+}
+
+#[test]
+fn test_parse_error() {
+	assert_eq!(
+		parse_input("987654321111111\n8111a1111111119").err(),
+		Some(ParseError::new(2, 5, "a", "a digit"))
+	);
+}
+
+#[test]
+fn test_too_few_batteries() {
+	assert_eq!(Day3.part1("98765\n11119").unwrap(), 98 + 19);
+	assert_eq!(
+		Day3.part2("987654321111111\n98765")
+			.unwrap_err()
+			.to_string(),
+		"no answer: bank 2 has 5 batteries but 12 have to be turned on"
+	);
+	assert_eq!(
+		part1(parse_input("98765\n\n11119").unwrap()),
+		Err(TooFewBatteries {
+			bank: 2,
+			batteries: 0,
+			needed: 2
+		})
+	);
+}
@@ -1,144 +1,408 @@
-const INPUT: &str = include_str!("../inputs/day4.txt");
+use std::fmt;
+
+use crate::{
+	bench::{self, DayTimings},
+	grid::Grid,
+	parse::ParseError,
+	solution::{Part, Solution, SolveError},
+};
+
+pub const INPUT: &str = include_str!("../inputs/day4.txt");
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
-enum Cell {
+pub enum Cell {
 	Empty,
 	Roll,
 	RemoveableRoll,
 }
 
 impl Cell {
-	fn new(c: u8) -> Self {
+	pub fn new(c: char) -> Option<Self> {
 		match c {
-			b'.' => Cell::Empty,
-			b'@' => Cell::Roll,
-			b'x' => Cell::RemoveableRoll,
-			_ => panic!("Invalid cell character: {}", c as char),
+			'.' => Some(Cell::Empty),
+			'@' => Some(Cell::Roll),
+			'x' => Some(Cell::RemoveableRoll),
+			_ => None,
 		}
 	}
 
-	fn is_roll(&self) -> bool {
+	pub fn is_roll(&self) -> bool {
 		matches!(self, Cell::Roll | Cell::RemoveableRoll)
 	}
 }
 
-#[derive(Debug)]
-struct Map {
-	inner: Vec<Cell>,
-	width: usize,
-	height: usize,
+impl fmt::Display for Cell {
+	fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+		f.write_str(match self {
+			Cell::Empty => ".",
+			Cell::Roll => "@",
+			Cell::RemoveableRoll => "x",
+		})
+	}
+}
+
+/// Which cells around a roll count as its neighbours.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Neighbourhood {
+	/// All cells within a square of the given radius, radius 1 being the
+	/// 8 surrounding cells.
+	Moore(usize),
+	/// All cells within the given manhattan distance, radius 1 being the
+	/// 4 horizontally and vertically adjacent cells.
+	VonNeumann(usize),
+	/// Arbitrary `(dx, dy)` offsets.
+	Custom(Vec<(isize, isize)>),
+}
+
+impl Neighbourhood {
+	pub fn offsets(&self) -> Vec<(isize, isize)> {
+		let square = |r: usize| {
+			let r = r as isize;
+			(-r..=r)
+				.flat_map(move |dy| (-r..=r).map(move |dx| (dx, dy)))
+				.filter(|&d| d != (0, 0))
+		};
+
+		match self {
+			Neighbourhood::Moore(r) => square(*r).collect(),
+			Neighbourhood::VonNeumann(r) => square(*r)
+				.filter(|(dx, dy)| dx.unsigned_abs() + dy.unsigned_abs() <= *r)
+				.collect(),
+			Neighbourhood::Custom(offsets) => offsets.clone(),
+		}
+	}
+}
+
+/// Decides when a roll is accessible.
+///
+/// A roll is accessible if fewer than `threshold` of its neighbours are
+/// rolls, the puzzle uses a threshold of 4 with the 8 surrounding cells.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Rule {
+	offsets: Vec<(isize, isize)>,
+	threshold: usize,
+	wrap: bool,
+}
+
+impl Rule {
+	pub fn new(neighbourhood: Neighbourhood, threshold: usize) -> Self {
+		Self {
+			offsets: neighbourhood.offsets(),
+			threshold,
+			wrap: false,
+		}
+	}
+
+	/// If set, neighbours wrap around the edges of the map.
+	pub fn wrapping(mut self, wrap: bool) -> Self {
+		self.wrap = wrap;
+		self
+	}
+
+	pub fn offsets(&self) -> &[(isize, isize)] {
+		&self.offsets
+	}
+
+	pub fn threshold(&self) -> usize {
+		self.threshold
+	}
+
+	pub fn wraps(&self) -> bool {
+		self.wrap
+	}
+}
+
+impl Default for Rule {
+	fn default() -> Self {
+		Self::new(Neighbourhood::Moore(1), 4)
+	}
+}
+
+#[derive(Debug, Clone)]
+pub struct Map {
+	grid: Grid<Cell>,
+	rule: Rule,
 }
 
 impl Map {
-	fn remove_accessible(&mut self) -> u64 {
-		let mut count = 0;
+	/// Replaces the default puzzle rule.
+	pub fn with_rule(mut self, rule: Rule) -> Self {
+		self.rule = rule;
+		self
+	}
 
-		for y in 0..self.height {
-			for x in 0..self.width {
-				let idx = y * self.width + x;
+	pub fn grid(&self) -> &Grid<Cell> {
+		&self.grid
+	}
 
-				if self.inner[idx].is_roll() && self.is_accessible(x, y) {
-					self.inner[idx] = Cell::RemoveableRoll;
-					count += 1;
-				}
+	pub fn rule(&self) -> &Rule {
+		&self.rule
+	}
+
+	/// The positions of all neighbours of `(x, y)` according to the rule.
+	pub fn neighbours(
+		&self,
+		x: usize,
+		y: usize,
+	) -> Box<dyn Iterator<Item = (usize, usize)> + '_> {
+		let offsets = self.rule.offsets();
+
+		if self.rule.wrap {
+			Box::new(self.grid.wrapping_offset_positions(x, y, offsets))
+		} else {
+			Box::new(self.grid.offset_positions(x, y, offsets))
+		}
+	}
+
+	pub fn remove_accessible(&mut self) -> u64 {
+		let mut count = 0;
+
+		for (x, y) in self.grid.positions() {
+			if self.grid[(x, y)].is_roll() && self.is_accessible(x, y) {
+				self.grid[(x, y)] = Cell::RemoveableRoll;
+				count += 1;
 			}
 		}
 
 		count
 	}
 
-	fn clean_removeable(&mut self) {
-		for c in &mut self.inner {
+	/// The positions currently marked as [`Cell::RemoveableRoll`].
+	pub fn removeable(&self) -> impl Iterator<Item = (usize, usize)> {
+		self.grid
+			.iter()
+			.filter(|(_, c)| **c == Cell::RemoveableRoll)
+			.map(|(pos, _)| pos)
+	}
+
+	pub fn clean_removeable(&mut self) {
+		for c in self.grid.cells_mut() {
 			if *c == Cell::RemoveableRoll {
 				*c = Cell::Empty;
 			}
 		}
 	}
 
-	fn is_accessible(&self, x: usize, y: usize) -> bool {
-		#[rustfmt::skip]
-		const DISPLACEMENT: &[(i32, i32); 8] = &[
-			(-1, -1), (-1, 0), (-1, 1),
-			(0, -1),           (0, 1),
-			(1, -1),  (1, 0),  (1, 1),
-		];
+	/// How many of the neighbours of `(x, y)` are rolls.
+	pub fn adjacent_rolls(&self, x: usize, y: usize) -> usize {
+		let offsets = self.rule.offsets();
 
-		let adjacent = DISPLACEMENT
-			.iter()
-			.filter_map(|(dx, dy)| {
-				let nx = usize::try_from(x as i32 + dx)
-					.ok()
-					.filter(|&nx| nx < self.width)?;
-				let ny = usize::try_from(y as i32 + dy).ok()?;
+		// not using `neighbours` to avoid the allocation
+		if self.rule.wrap {
+			self.count_rolls(self.grid.wrapping_offset_positions(x, y, offsets))
+		} else {
+			self.count_rolls(self.grid.offset_positions(x, y, offsets))
+		}
+	}
 
-				self.inner.get(ny * self.width + nx).filter(|c| c.is_roll())
-			})
-			.count();
+	fn count_rolls(
+		&self,
+		positions: impl Iterator<Item = (usize, usize)>,
+	) -> usize {
+		positions.filter(|&pos| self.grid[pos].is_roll()).count()
+	}
 
-		adjacent < 4
+	pub fn is_accessible(&self, x: usize, y: usize) -> bool {
+		self.adjacent_rolls(x, y) < self.rule.threshold
 	}
 }
 
-fn parse_input(input: &str) -> Map {
-	let mut width = 0;
+impl fmt::Display for Map {
+	fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+		self.grid.fmt(f)
+	}
+}
+
+/// The full removal cascade, recording which rolls got removed in which
+/// round.
+#[derive(Debug, Clone)]
+pub struct Simulation {
+	initial: Map,
+	rounds: Vec<Vec<(usize, usize)>>,
+	stable: Map,
+}
+
+impl Simulation {
+	/// Removes accessible rolls round by round until nothing changes anymore.
+	///
+	/// Instead of rescanning the whole map every round, this keeps the
+	/// neighbour count of every roll up to date and only looks at the rolls
+	/// around the ones removed in the previous round.
+	pub fn run(initial: Map) -> Self {
+		let mut map = initial.clone();
+		let threshold = map.rule.threshold;
+		// a removed roll is a neighbour of the rolls at the opposite offsets,
+		// which matters for asymmetric custom neighbourhoods
+		let reverse = map
+			.rule
+			.offsets()
+			.iter()
+			.map(|&(dx, dy)| (-dx, -dy))
+			.collect::<Vec<_>>();
+
+		let mut counts = map.grid.map(|_| 0);
+		// set once a roll is part of a round so it never gets queued twice
+		let mut queued = map.grid.map(|_| false);
+		let mut round = Vec::new();
+
+		for (pos, cell) in map.grid.iter() {
+			if cell.is_roll() {
+				counts[pos] = map.adjacent_rolls(pos.0, pos.1);
+				if counts[pos] < threshold {
+					queued[pos] = true;
+					round.push(pos);
+				}
+			}
+		}
+
+		let mut rounds = Vec::new();
+		while !round.is_empty() {
+			// same order as a row-major scan of the map
+			round.sort_unstable_by_key(|&(x, y)| (y, x));
+			for &pos in &round {
+				map.grid[pos] = Cell::Empty;
+			}
+
+			let mut next = Vec::new();
+			for &(x, y) in &round {
+				let mut update = |pos| {
+					if !map.grid[pos].is_roll() {
+						return;
+					}
 
-	let inner = input
-		.trim()
-		.lines()
-		.map(|l| {
-			let bytes = l.as_bytes();
-			if width != bytes.len() {
-				assert!(width == 0);
-				width = bytes.len();
+					counts[pos] -= 1;
+					if counts[pos] < threshold && !queued[pos] {
+						queued[pos] = true;
+						next.push(pos);
+					}
+				};
+
+				if map.rule.wrap {
+					map.grid
+						.wrapping_offset_positions(x, y, &reverse)
+						.for_each(&mut update);
+				} else {
+					map.grid
+						.offset_positions(x, y, &reverse)
+						.for_each(&mut update);
+				}
 			}
 
-			bytes.iter().map(|&b| Cell::new(b))
-		})
-		.flatten()
-		.collect::<Vec<_>>();
+			rounds.push(std::mem::replace(&mut round, next));
+		}
 
-	Map {
-		width,
-		height: inner.len() / width,
-		inner,
+		Self {
+			initial,
+			rounds,
+			stable: map,
+		}
+	}
+
+	pub fn initial(&self) -> &Map {
+		&self.initial
+	}
+
+	/// The map after the last round, where no roll is accessible anymore.
+	pub fn stable(&self) -> &Map {
+		&self.stable
+	}
+
+	/// The positions removed in each round.
+	pub fn rounds(&self) -> &[Vec<(usize, usize)>] {
+		&self.rounds
+	}
+
+	pub fn round_counts(&self) -> impl Iterator<Item = u64> {
+		self.rounds.iter().map(|r| r.len() as u64)
+	}
+
+	pub fn total_removed(&self) -> u64 {
+		self.round_counts().sum()
+	}
+
+	/// In which round each cell got removed, `None` if it never was.
+	pub fn removal_rounds(&self) -> Grid<Option<usize>> {
+		let mut grid = self.initial.grid.map(|_| None);
+
+		for (round, removed) in self.rounds.iter().enumerate() {
+			for &pos in removed {
+				grid[pos] = Some(round);
+			}
+		}
+
+		grid
+	}
+
+	/// The map at the start of `round` with all rolls removed in that round
+	/// marked as [`Cell::RemoveableRoll`].
+	///
+	/// ## Panics
+	/// If `round` is out of bounds.
+	pub fn render_round(&self, round: usize) -> Map {
+		let mut map = self.initial.clone();
+
+		for removed in &self.rounds[..round] {
+			for &pos in removed {
+				map.grid[pos] = Cell::Empty;
+			}
+		}
+
+		for &pos in &self.rounds[round] {
+			map.grid[pos] = Cell::RemoveableRoll;
+		}
+
+		map
 	}
 }
 
-fn part1() -> u64 {
-	let mut map = parse_input(INPUT);
+pub fn parse_input(input: &str) -> Result<Map, ParseError> {
+	let grid = Grid::parse(input, "`.`, `@` or `x`", Cell::new)?;
 
+	Ok(Map {
+		grid,
+		rule: Rule::default(),
+	})
+}
+
+pub fn part1(mut map: Map) -> u64 {
 	map.remove_accessible()
 }
 
-fn part2() -> u64 {
-	let mut map = parse_input(INPUT);
+pub fn part2(map: Map) -> u64 {
+	Simulation::run(map).total_removed()
+}
 
-	let mut count = 0;
+pub struct Day4;
 
-	loop {
-		let removeable = map.remove_accessible();
-		if removeable == 0 {
-			return count;
-		}
+impl Solution for Day4 {
+	fn day(&self) -> u32 {
+		4
+	}
 
-		count += removeable;
-		map.clean_removeable();
+	fn input(&self) -> &'static str {
+		INPUT
+	}
+
+	fn part1(&self, input: &str) -> Result<u64, SolveError> {
+		Ok(part1(parse_input(input)?))
 	}
-}
 
-fn main() {
-	let p1 = part1();
-	println!("Part 1: {p1}");
-	assert_eq!(p1, 1604);
+	fn part2(&self, input: &str) -> Result<u64, SolveError> {
+		Ok(part2(parse_input(input)?))
+	}
 
-	let p2 = part2();
-	println!("Part 2: {p2}");
+	fn bench(
+		&self,
+		input: &str,
+		runs: usize,
+		parts: &[Part],
+	) -> Result<DayTimings, ParseError> {
+		bench::phases(input, runs, parts, parse_input, part1, part2)
+	}
 }
 
-#[test]
-fn test_part1() {
-	let mut map = parse_input(
-		"\
+#[cfg(test)]
+const EXAMPLE: &str = "\
 ..@@.@@@@.
 @@@.@.@.@@
 @@@@@.@.@@
@@ -149,8 +413,144 @@ fn test_part1() {
 @.@@@.@@@@
 .@@@@@@@@.
 @.@.@@@.@.
-",
-	);
+";
+
+/// The rounds of the cascade computed by rescanning the whole map each round.
+#[cfg(test)]
+fn rescan_rounds(mut map: Map) -> Vec<Vec<(usize, usize)>> {
+	let mut rounds = Vec::new();
+
+	while map.remove_accessible() > 0 {
+		rounds.push(map.removeable().collect());
+		map.clean_removeable();
+	}
+
+	rounds
+}
+
+#[test]
+fn test_part1() {
+	let mut map = parse_input(EXAMPLE).unwrap();
 
 	assert_eq!(map.remove_accessible(), 13);
 }
+
+#[test]
+fn test_simulation() {
+	let sim = Simulation::run(parse_input(EXAMPLE).unwrap());
+
+	assert_eq!(
+		sim.round_counts().collect::<Vec<_>>(),
+		[13, 12, 7, 5, 2, 1, 1, 1, 1]
+	);
+	assert_eq!(sim.total_removed(), 43);
+
+	assert_eq!(
+		sim.render_round(0).to_string(),
+		"\
+..xx.xx@x.
+x@@.@.@.@@
+@@@@@.x.@@
+@.@@@@..@.
+x@.@@@@.@x
+.@@@@@@@.@
+.@.@.@.@@@
+x.@@@.@@@@
+.@@@@@@@@.
+x.x.@@@.x."
+	);
+
+	let removal_rounds = sim.removal_rounds();
+	assert_eq!(removal_rounds[(2, 0)], Some(0));
+	assert_eq!(removal_rounds[(0, 1)], Some(0));
+	assert_eq!(removal_rounds[(4, 1)], Some(1));
+	assert_eq!(removal_rounds[(1, 1)], Some(2));
+	assert_eq!(removal_rounds[(3, 4)], None);
+
+	// every removed roll is gone in the stable map
+	for (pos, round) in removal_rounds.iter() {
+		if round.is_some() {
+			assert_eq!(sim.stable().grid()[pos], Cell::Empty);
+		}
+	}
+}
+
+#[test]
+fn test_rules() {
+	assert_eq!(Neighbourhood::Moore(1).offsets().len(), 8);
+	assert_eq!(Neighbourhood::Moore(2).offsets().len(), 24);
+	assert_eq!(Neighbourhood::VonNeumann(1).offsets().len(), 4);
+	assert_eq!(Neighbourhood::VonNeumann(2).offsets().len(), 12);
+
+	let map = || parse_input(EXAMPLE).unwrap();
+	assert_eq!(part2(map()), 43);
+
+	// with 4 neighbours only fully enclosed rolls stay, which the cascade
+	// eventually removes as well
+	let rule = Rule::new(Neighbourhood::VonNeumann(1), 4);
+	let sim = Simulation::run(map().with_rule(rule));
+	assert_eq!(
+		sim.stable().grid().cells(),
+		map().grid().map(|_| Cell::Empty).cells()
+	);
+
+	// on a torus the border rolls get additional neighbours
+	let mut wrapped = map().with_rule(Rule::default().wrapping(true));
+	assert!(wrapped.remove_accessible() < 13);
+
+	let rule = Rule::new(Neighbourhood::Custom(vec![(1, 0)]), 1);
+	let mut right = map().with_rule(rule);
+	// every roll without a roll to its right
+	assert_eq!(right.remove_accessible(), 29);
+}
+
+#[test]
+fn test_parse_error() {
+	assert_eq!(
+		parse_input("..@@\n@@#@").unwrap_err(),
+		ParseError::new(2, 3, "#", "`.`, `@` or `x`")
+	);
+	assert_eq!(
+		parse_input("..@@\n@@@").unwrap_err(),
+		ParseError::new(2, 1, "@@@", "a line of width 4")
+	);
+}
+
+#[test]
+fn test_incremental() {
+	let map = || parse_input(EXAMPLE).unwrap();
+	let rules = [
+		Rule::default(),
+		Rule::default().wrapping(true),
+		Rule::new(Neighbourhood::Moore(2), 12),
+		Rule::new(Neighbourhood::VonNeumann(1), 3),
+		Rule::new(Neighbourhood::Custom(vec![(1, 0), (2, 1)]), 2),
+		Rule::new(Neighbourhood::Custom(vec![(7, -3)]), 1).wrapping(true),
+	];
+
+	for rule in rules {
+		let map = map().with_rule(rule);
+		assert_eq!(Simulation::run(map.clone()).rounds(), rescan_rounds(map));
+	}
+
+	// a larger pseudo random map with roughly 70% rolls
+	let mut state = 0x2545_f491_4f6c_dd1du64;
+	let grid = Grid::from_fn(150, 120, |_, _| {
+		state ^= state << 13;
+		state ^= state >> 7;
+		state ^= state << 17;
+		if state % 10 < 7 {
+			Cell::Roll
+		} else {
+			Cell::Empty
+		}
+	});
+	let map = Map {
+		grid,
+		rule: Rule::default(),
+	};
+
+	let sim = Simulation::run(map.clone());
+	assert!(sim.rounds().len() > 10);
+	assert_eq!(sim.rounds(), rescan_rounds(map));
+}
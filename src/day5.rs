@@ -1,82 +1,51 @@
-use std::{collections::VecDeque, mem, ops::Range};
+use std::ops::Range;
 
-const INPUT: &str = include_str!("../inputs/day5.txt");
+use crate::{
+	bench::{self, DayTimings},
+	interval::IntervalSet,
+	parse::ParseError,
+	solution::{Part, Solution, SolveError},
+};
 
-struct FreshIngredients {
-	inner: Vec<Range<u64>>,
-	dedup: bool,
+pub const INPUT: &str = include_str!("../inputs/day5.txt");
+
+#[derive(Debug, Clone)]
+pub struct FreshIngredients {
+	inner: IntervalSet<u64>,
 }
 
 impl FreshIngredients {
-	fn dedup(&mut self) {
-		let mut nv = VecDeque::<Range<u64>>::with_capacity(self.inner.len());
-
-		let mut ranges = mem::take(&mut self.inner);
-
-		'range: while let Some(mut r) = ranges.pop() {
-			for i in 0..nv.len() {
-				let nr = nv[i].clone();
-
-				if r.start <= nr.start {
-					if r.end <= nr.start {
-						// we are completely before
-						nv.insert(i, r);
-						continue 'range;
-					}
-
-					// we overlap at the start
-					nv[i].start = r.start;
-
-					if r.end <= nr.end {
-						// we are completely inside
-						continue 'range;
-					}
-
-					ranges.push(Range {
-						start: nr.end,
-						end: r.end,
-					});
-
-					continue 'range;
-				}
-
-				assert!(r.start > nr.start);
-
-				if r.start < nr.end {
-					if r.end <= nr.end {
-						// we are completely inside
-						continue 'range;
-					}
-
-					// we overlap at the end
-					r.start = nr.end;
-				}
-			}
-
-			nv.push_back(r);
+	pub fn new(ranges: Vec<Range<u64>>) -> Self {
+		Self {
+			inner: ranges.into_iter().collect(),
 		}
-
-		// todo we could reduce the ranges
-		self.inner = nv.into_iter().collect();
-		self.dedup = true;
 	}
 
-	fn count_fresh(&self) -> u64 {
-		assert!(self.dedup);
+	pub fn ranges(&self) -> &IntervalSet<u64> {
+		&self.inner
+	}
 
-		self.inner.iter().map(|r| r.end - r.start).sum()
+	pub fn count_fresh(&self) -> u64 {
+		self.inner.size()
 	}
 
-	fn count_fresh_from_ids(&self, ids: impl Iterator<Item = u64>) -> u64 {
+	pub fn count_fresh_from_ids(&self, ids: impl Iterator<Item = u64>) -> u64 {
 		ids.filter(|id| self.is_fresh(*id)).count() as u64
 	}
 
-	fn is_fresh(&self, id: u64) -> bool {
-		self.inner.iter().any(|r| r.contains(&id))
+	pub fn is_fresh(&self, id: u64) -> bool {
+		self.inner.contains(&id)
 	}
 }
 
-fn parse_input(input: &str) -> (FreshIngredients, impl Iterator<Item = u64>) {
+fn parse_id(input: &str, id: &str) -> Result<u64, ParseError> {
+	id.parse()
+		.map_err(|_| ParseError::at(input, id, "an ingredient id"))
+}
+
+pub fn parse_input(
+	input: &str,
+) -> Result<(FreshIngredients, Vec<u64>), ParseError> {
 	let mut lines = input.trim().lines();
 
 	let mut fresh = Vec::new();
@@ -87,44 +56,68 @@ fn parse_input(input: &str) -> (FreshIngredients, impl Iterator<Item = u64>) {
 			break;
 		}
 
-		let mut parts = line.split('-');
+		let (start, end) = line.split_once('-').ok_or_else(|| {
+			ParseError::at(input, line, "a range `start-end`")
+		})?;
+
+		let (start, end_id) = (parse_id(input, start)?, parse_id(input, end)?);
+		if end_id < start {
+			return Err(ParseError::at(
+				input,
+				end,
+				"an end not below the start",
+			));
+		}
+		// ranges are stored exclusive, so the last id has no end
+		let end = end_id.checked_add(1).ok_or_else(|| {
+			ParseError::at(input, end, format!("an id below {}", u64::MAX))
+		})?;
 
-		fresh.push(Range {
-			start: parts.next().unwrap().parse::<u64>().unwrap(),
-			end: parts.next().unwrap().parse::<u64>().unwrap() + 1,
-		});
+		fresh.push(start..end);
 	}
 
-	(
-		FreshIngredients {
-			inner: fresh,
-			dedup: false,
-		},
-		lines.map(|l| l.parse::<u64>().unwrap()),
-	)
-}
-
-fn part1() -> u64 {
-	let (fresh, available) = parse_input(INPUT);
+	let available = lines
+		.map(|l| parse_id(input, l))
+		.collect::<Result<_, _>>()?;
 
-	fresh.count_fresh_from_ids(available)
+	Ok((FreshIngredients::new(fresh), available))
 }
 
-fn part2() -> u64 {
-	let (mut fresh, _) = parse_input(INPUT);
+pub fn part1((fresh, available): (FreshIngredients, Vec<u64>)) -> u64 {
+	fresh.count_fresh_from_ids(available.into_iter())
+}
 
-	fresh.dedup();
+pub fn part2((fresh, _): (FreshIngredients, Vec<u64>)) -> u64 {
 	fresh.count_fresh()
 }
 
-fn main() {
-	let p1 = part1();
-	println!("Part 1: {p1}");
-	assert_eq!(p1, 513);
+pub struct Day5;
 
-	let p2 = part2();
-	println!("Part 2: {p2}");
-	assert_eq!(p2, 339668510830757);
+impl Solution for Day5 {
+	fn day(&self) -> u32 {
+		5
+	}
+
+	fn input(&self) -> &'static str {
+		INPUT
+	}
+
+	fn part1(&self, input: &str) -> Result<u64, SolveError> {
+		Ok(part1(parse_input(input)?))
+	}
+
+	fn part2(&self, input: &str) -> Result<u64, SolveError> {
+		Ok(part2(parse_input(input)?))
+	}
+
+	fn bench(
+		&self,
+		input: &str,
+		runs: usize,
+		parts: &[Part],
+	) -> Result<DayTimings, ParseError> {
+		bench::phases(input, runs, parts, parse_input, part1, part2)
+	}
 }
 
 #[test]
@@ -142,29 +135,30 @@ fn test_part1() {
 11
 17
 32",
-	);
+	)
+	.unwrap();
 
-	assert_eq!(fresh.count_fresh_from_ids(available), 3);
+	assert_eq!(fresh.count_fresh_from_ids(available.into_iter()), 3);
 }
 
 #[test]
 fn test_part2() {
-	let (mut fresh, _) = parse_input(
+	let (fresh, _) = parse_input(
 		"\
 3-5
 10-14
 16-20
 12-18",
-	);
+	)
+	.unwrap();
 
-	fresh.dedup();
-	eprintln!("Fresh ranges: {:?}", fresh.inner);
+	eprintln!("Fresh ranges: {:?}", fresh.ranges());
 	assert_eq!(fresh.count_fresh(), 14);
 }
 
 #[test]
 fn test_overlap_check() {
-	let (mut fresh, _) = parse_input(
+	let (fresh, _) = parse_input(
 		"\
 5-15
 15-20
@@ -174,8 +168,33 @@ fn test_overlap_check() {
 10-25
 30-40
 ",
-	);
+	)
+	.unwrap();
 
-	fresh.dedup();
 	assert_eq!(fresh.count_fresh(), 37);
 }
+
+#[test]
+fn test_parse_error() {
+	assert_eq!(
+		parse_input("3-5\n10 14\n\n1").err(),
+		Some(ParseError::new(2, 1, "10 14", "a range `start-end`"))
+	);
+	assert_eq!(
+		parse_input("3-5\n\n1\n-5").err(),
+		Some(ParseError::new(4, 1, "-5", "an ingredient id"))
+	);
+	assert_eq!(
+		parse_input("3-5\n5-3\n\n1").err(),
+		Some(ParseError::new(2, 3, "3", "an end not below the start"))
+	);
+	assert_eq!(
+		parse_input("1-18446744073709551615\n\n1").err(),
+		Some(ParseError::new(
+			1,
+			3,
+			"18446744073709551615",
+			"an id below 18446744073709551615"
+		))
+	);
+}
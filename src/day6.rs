@@ -1,21 +1,36 @@
-const INPUT: &str = include_str!("../inputs/day6.txt");
+use crate::{
+	bench::{self, DayTimings},
+	parse::ParseError,
+	solution::{Part, Solution, SolveError},
+};
 
-#[derive(Debug)]
-enum Symbol {
+pub const INPUT: &str = include_str!("../inputs/day6.txt");
+
+#[derive(Debug, Clone)]
+pub enum Symbol {
 	Add,
 	Mul,
 }
 
 impl Symbol {
-	fn new(byte: u8) -> Self {
+	pub fn new(byte: u8) -> Option<Self> {
 		match byte {
-			b'+' => Symbol::Add,
-			b'*' => Symbol::Mul,
-			_ => panic!("Invalid symbol byte: {}", byte as char),
+			b'+' => Some(Symbol::Add),
+			b'*' => Some(Symbol::Mul),
+			_ => None,
 		}
 	}
 
-	fn calc(&self, nums: impl Iterator<Item = u64>) -> u64 {
+	/// Parses `s` which needs to be a single symbol.
+	pub fn parse(input: &str, s: &str) -> Result<Self, ParseError> {
+		match s.as_bytes() {
+			[byte] => Symbol::new(*byte),
+			_ => None,
+		}
+		.ok_or_else(|| ParseError::at(input, s, "`+` or `*`"))
+	}
+
+	pub fn calc(&self, nums: impl Iterator<Item = u64>) -> u64 {
 		match self {
 			Symbol::Add => nums.sum(),
 			Symbol::Mul => nums.product(),
@@ -23,8 +38,8 @@ impl Symbol {
 	}
 }
 
-#[derive(Debug)]
-struct ProblemsP1 {
+#[derive(Debug, Clone)]
+pub struct ProblemsP1 {
 	numbers: Vec<Vec<u64>>,
 	symbols: Vec<Symbol>,
 }
@@ -38,8 +53,9 @@ impl ProblemsP1 {
 	}
 }
 
-fn parse_input_p1(input: &str) -> ProblemsP1 {
+pub fn parse_input_p1(input: &str) -> Result<ProblemsP1, ParseError> {
 	let mut numbers = Vec::new();
+	let mut number_lines = Vec::new();
 	let mut symbols = Vec::new();
 
 	let mut iter = input.trim().lines();
@@ -47,33 +63,53 @@ fn parse_input_p1(input: &str) -> ProblemsP1 {
 	for line in &mut iter {
 		let line = line.trim();
 
-		if line.as_bytes()[0].is_ascii_digit() {
+		if !symbols.is_empty() {
+			return Err(ParseError::at(input, line, "the end of the input"));
+		}
+
+		if line.starts_with(|c: char| c.is_ascii_digit()) {
 			numbers.push(
 				line.split_ascii_whitespace()
-					.map(|n| n.parse().unwrap())
-					.collect(),
+					.map(|n| {
+						n.parse()
+							.map_err(|_| ParseError::at(input, n, "a number"))
+					})
+					.collect::<Result<Vec<_>, _>>()?,
 			);
+			number_lines.push(line);
 		} else {
-			assert!(symbols.is_empty());
-
 			symbols = line
 				.split_ascii_whitespace()
-				.map(|b| Symbol::new(b.as_bytes()[0]))
-				.collect();
+				.map(|b| Symbol::parse(input, b))
+				.collect::<Result<_, _>>()?;
 		}
 	}
 
-	ProblemsP1 { numbers, symbols }
-}
+	if symbols.is_empty() {
+		let end = &input[input.len()..];
+		return Err(ParseError::at(input, end, "a line of symbols"));
+	}
 
-fn part1() -> u64 {
-	let problems = parse_input_p1(INPUT);
+	// every problem needs a number from every line
+	for (nums, line) in numbers.iter().zip(number_lines) {
+		if nums.len() != symbols.len() {
+			return Err(ParseError::at(
+				input,
+				line,
+				format!("{} numbers", symbols.len()),
+			));
+		}
+	}
 
+	Ok(ProblemsP1 { numbers, symbols })
+}
+
+pub fn part1(problems: ProblemsP1) -> u64 {
 	problems.solutions().sum()
 }
 
-#[derive(Debug)]
-struct ProblemsP2<'a> {
+#[derive(Debug, Clone)]
+pub struct ProblemsP2<'a> {
 	widths: Vec<usize>,
 	numbers: Vec<Vec<&'a [u8]>>,
 	symbols: Vec<Symbol>,
@@ -100,18 +136,25 @@ impl<'a> ProblemsP2<'a> {
 	}
 }
 
-fn parse_input_p2<'a>(input: &'a str) -> ProblemsP2<'a> {
-	let input = input.trim_matches('\n');
+pub fn parse_input_p2(input: &str) -> Result<ProblemsP2<'_>, ParseError> {
+	let trimmed = input.trim_matches('\n');
 
 	let mut widths = Vec::new();
 	let mut symbols = Vec::new();
 
-	let last_line = input.lines().rev().next().unwrap();
+	let last_line = trimmed.lines().next_back().unwrap_or(trimmed);
+
+	if !last_line.starts_with(['+', '*']) {
+		return Err(ParseError::at_char(input, last_line, "`+` or `*`"));
+	}
 
 	let mut curr_width = 0;
-	for byte in last_line.as_bytes() {
+	for (i, byte) in last_line.bytes().enumerate() {
 		if !byte.is_ascii_whitespace() {
-			symbols.push(Symbol::new(*byte));
+			let symbol = Symbol::new(byte).ok_or_else(|| {
+				ParseError::at_char(input, &last_line[i..], "`+` or `*`")
+			})?;
+			symbols.push(symbol);
 			if curr_width != 0 {
 				// -1 because there is always a whitespace between symbols
 				// +1 because the symbol itself takes one space
@@ -125,13 +168,27 @@ fn parse_input_p2<'a>(input: &'a str) -> ProblemsP2<'a> {
 	// +1 for the symbol
 	widths.push(curr_width + 1);
 
+	let line_width = widths.iter().sum::<usize>() + widths.len() - 1;
+
 	let mut numbers: Vec<Vec<_>> = Vec::new();
 
-	for line in input.lines() {
-		if !line.trim_start().as_bytes()[0].is_ascii_digit() {
+	for line in trimmed.lines() {
+		if !line.trim_start().starts_with(|c: char| c.is_ascii_digit()) {
 			break;
 		}
 
+		if line.len() < line_width {
+			return Err(ParseError::at(
+				input,
+				line,
+				format!("a line of width {line_width}"),
+			));
+		}
+
+		if let Some(i) = line.find(|c: char| c != ' ' && !c.is_ascii_digit()) {
+			return Err(ParseError::at_char(input, &line[i..], "a digit"));
+		}
+
 		let line = line.as_bytes();
 
 		let mut idx = 0;
@@ -147,33 +204,58 @@ fn parse_input_p2<'a>(input: &'a str) -> ProblemsP2<'a> {
 		);
 	}
 
-	ProblemsP2 {
+	Ok(ProblemsP2 {
 		widths,
 		numbers,
 		symbols,
-	}
+	})
 }
 
-fn part2() -> u64 {
-	let problems = parse_input_p2(INPUT);
-
+pub fn part2(problems: ProblemsP2<'_>) -> u64 {
 	problems.solutions().sum()
 }
 
-fn main() {
-	let p1 = part1();
-	println!("Part 1: {p1}");
-	assert_eq!(p1, 4309240495780);
+pub struct Day6;
 
-	let p2 = part2();
-	println!("Part 2: {p2}");
-	assert_eq!(p2, 9170286552289);
+impl Solution for Day6 {
+	fn day(&self) -> u32 {
+		6
+	}
+
+	fn input(&self) -> &'static str {
+		INPUT
+	}
+
+	fn part1(&self, input: &str) -> Result<u64, SolveError> {
+		Ok(part1(parse_input_p1(input)?))
+	}
+
+	fn part2(&self, input: &str) -> Result<u64, SolveError> {
+		Ok(part2(parse_input_p2(input)?))
+	}
+
+	fn bench(
+		&self,
+		input: &str,
+		runs: usize,
+		parts: &[Part],
+	) -> Result<DayTimings, ParseError> {
+		bench::phases_per_part(
+			input,
+			runs,
+			parts,
+			parse_input_p1,
+			part1,
+			parse_input_p2,
+			part2,
+		)
+	}
 }
 
 #[test]
 fn test_p2() {
 	#[rustfmt::skip]
-	let problems = parse_input_p2("123 328  51 64 \n 45 64  387 23 \n  6 98  215 314\n*   +   *   +  ");
+	let problems = parse_input_p2("123 328  51 64 \n 45 64  387 23 \n  6 98  215 314\n*   +   *   +  ").unwrap();
 
 	let mut solutions = problems.solutions();
 	assert_eq!(solutions.next(), Some(8544));
@@ -182,3 +264,19 @@ fn test_p2() {
 	assert_eq!(solutions.next(), Some(1058));
 	assert_eq!(solutions.next(), None);
 }
+
+#[test]
+fn test_parse_error() {
+	assert_eq!(
+		parse_input_p1("123 328\n45 64 1\n* +").unwrap_err(),
+		ParseError::new(2, 1, "45 64 1", "2 numbers")
+	);
+	assert_eq!(
+		parse_input_p1("123 328\n* -").unwrap_err(),
+		ParseError::new(2, 3, "-", "`+` or `*`")
+	);
+	assert_eq!(
+		parse_input_p2("123 328\n 45 6\n*   +  ").unwrap_err(),
+		ParseError::new(2, 1, " 45 6", "a line of width 7")
+	);
+}
@@ -1,150 +1,690 @@
-const INPUT: &str = include_str!("../inputs/day7.txt");
+use std::{
+	error::Error,
+	fmt::{self, Write},
+};
+
+use crate::{
+	bench::{self, DayTimings},
+	count::{Count, Overflow},
+	grid::Grid,
+	parse::ParseError,
+	solution::{Part, Solution, SolveError},
+};
+
+pub const INPUT: &str = include_str!("../inputs/day7.txt");
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
-enum Symbol {
+pub enum Symbol {
 	Empty,
 	Start,
 	Splitter,
 	Tachion,
+	/// `/`, reflects a beam going down to the left
+	ForwardMirror,
+	/// `\`, reflects a beam going down to the right
+	BackMirror,
+	Absorber,
 }
 
 impl Symbol {
-	fn new(byte: &u8) -> Self {
-		match byte {
-			b'.' => Symbol::Empty,
-			b'S' => Symbol::Start,
-			b'^' => Symbol::Splitter,
-			b'|' => Symbol::Tachion,
-			_ => panic!("Unknown symbol: {}", *byte as char),
+	pub fn new(c: char) -> Option<Self> {
+		match c {
+			'.' => Some(Symbol::Empty),
+			'S' => Some(Symbol::Start),
+			'^' => Some(Symbol::Splitter),
+			'|' => Some(Symbol::Tachion),
+			'/' => Some(Symbol::ForwardMirror),
+			'\\' => Some(Symbol::BackMirror),
+			'#' => Some(Symbol::Absorber),
+			_ => None,
 		}
 	}
 }
 
-#[derive(Debug)]
-struct Map {
-	width: usize,
-	height: usize,
-	cells: Vec<Symbol>,
-	tachion_count: Vec<u64>,
+impl fmt::Display for Symbol {
+	fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+		f.write_str(match self {
+			Symbol::Empty => ".",
+			Symbol::Start => "S",
+			Symbol::Splitter => "^",
+			Symbol::Tachion => "|",
+			Symbol::ForwardMirror => "/",
+			Symbol::BackMirror => "\\",
+			Symbol::Absorber => "#",
+		})
+	}
 }
 
-impl Map {
-	// runs the tachion simulation
-	fn run(&mut self) {
-		for y in 1..self.height {
-			for x in 0..self.width {
-				let up_idx = (y - 1) * self.width + x;
-				let up = self.cells[up_idx];
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Direction {
+	Up,
+	Down,
+	Left,
+	Right,
+}
 
-				let idx = y * self.width + x;
-				let curr = &mut self.cells[idx];
+impl Direction {
+	fn delta(self) -> (isize, isize) {
+		match self {
+			Direction::Up => (0, -1),
+			Direction::Down => (0, 1),
+			Direction::Left => (-1, 0),
+			Direction::Right => (1, 0),
+		}
+	}
 
-				match (up, *curr) {
-					(Symbol::Start | Symbol::Tachion, Symbol::Splitter) => {
-						// at least one
-						let count = self.tachion_count[up_idx].max(1);
+	/// The direction after hitting `mirror`, which is unchanged for any other
+	/// symbol.
+	fn reflect(self, mirror: Symbol) -> Self {
+		use Direction::*;
 
-						let left_idx = (x > 0).then(|| y * self.width + x - 1);
-						if let Some(left_idx) = left_idx {
-							self.cells[left_idx] = Symbol::Tachion;
-							self.tachion_count[left_idx] += count;
-						}
+		match (mirror, self) {
+			(Symbol::ForwardMirror, Up) => Right,
+			(Symbol::ForwardMirror, Right) => Up,
+			(Symbol::ForwardMirror, Down) => Left,
+			(Symbol::ForwardMirror, Left) => Down,
+			(Symbol::BackMirror, Up) => Left,
+			(Symbol::BackMirror, Left) => Up,
+			(Symbol::BackMirror, Down) => Right,
+			(Symbol::BackMirror, Right) => Down,
+			_ => self,
+		}
+	}
+}
+
+/// A beam in a cell, heading to the next one.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Beam {
+	pub x: usize,
+	pub y: usize,
+	pub heading: Direction,
+}
+
+/// Where a beam goes next, see [`Beams::steps`].
+#[derive(Debug, Clone, Copy)]
+struct Step {
+	/// `None` if the beam leaves the manifold at the bottom, which ends one
+	/// timeline.
+	beam: Option<Beam>,
+	/// The splitter and the chosen fan-out offset if the beam got split.
+	split: Option<((usize, usize), isize)>,
+}
 
-						let right_idx = (x + 1 < self.width)
-							.then(|| y * self.width + x + 1);
-						if let Some(right_idx) = right_idx {
-							self.cells[right_idx] = Symbol::Tachion;
-							self.tachion_count[right_idx] += count;
+/// What happens to beams in a manifold.
+#[derive(Debug, Clone, Copy)]
+struct Beams<'a> {
+	cells: &'a Grid<Symbol>,
+	fan_out: &'a [isize],
+}
+
+/// All beams reachable from the starts, see [`Beams::explore`].
+struct Exploration {
+	/// Every beam after all the beams it leads to.
+	order: Vec<Beam>,
+	/// A cell which is part of a cycle, if there is one.
+	cycle: Option<(usize, usize)>,
+}
+
+impl Beams<'_> {
+	fn idx(&self, beam: Beam) -> usize {
+		self.cells.idx(beam.x, beam.y).unwrap() * 4 + beam.heading as usize
+	}
+
+	/// A beam for every `S`, heading down.
+	fn starts(&self) -> Vec<Beam> {
+		self.cells
+			.iter()
+			.filter(|(_, s)| **s == Symbol::Start)
+			.map(|((x, y), _)| Beam {
+				x,
+				y,
+				heading: Direction::Down,
+			})
+			.collect()
+	}
+
+	/// Moves `beam` into the next cell.
+	///
+	/// Beams leaving the manifold anywhere but the bottom, or hitting an
+	/// absorber, are lost and have no steps. A splitter puts a beam heading
+	/// down next to it for every fan-out offset instead, in the order of the
+	/// offsets.
+	fn steps(&self, beam: Beam) -> Vec<Step> {
+		let (dx, dy) = beam.heading.delta();
+		let x = beam.x.checked_add_signed(dx);
+		let y = beam.y.checked_add_signed(dy);
+
+		let Some((x, y)) = x.zip(y).filter(|&(x, _)| x < self.cells.width())
+		else {
+			return Vec::new();
+		};
+		if y == self.cells.height() {
+			return vec![Step {
+				beam: None,
+				split: None,
+			}];
+		}
+
+		match self.cells[(x, y)] {
+			Symbol::Absorber => Vec::new(),
+			Symbol::Splitter => self
+				.fan_out
+				.iter()
+				.filter_map(|&d| {
+					let nx = x.checked_add_signed(d)?;
+					(nx < self.cells.width()).then_some(Step {
+						beam: Some(Beam {
+							x: nx,
+							y,
+							heading: Direction::Down,
+						}),
+						split: Some(((x, y), d)),
+					})
+				})
+				.collect(),
+			symbol => vec![Step {
+				beam: Some(Beam {
+					x,
+					y,
+					heading: beam.heading.reflect(symbol),
+				}),
+				split: None,
+			}],
+		}
+	}
+
+	/// Finds all beams reachable from `starts` with a depth first search.
+	fn explore(&self, starts: &[Beam]) -> Exploration {
+		#[derive(Clone, Copy, PartialEq, Eq)]
+		enum Visit {
+			New,
+			Active,
+			Done,
+		}
+
+		let mut visits = vec![Visit::New; self.cells.len() * 4];
+		let mut order = Vec::new();
+		let mut cycle = None;
+
+		for &start in starts {
+			if visits[self.idx(start)] != Visit::New {
+				continue;
+			}
+
+			visits[self.idx(start)] = Visit::Active;
+			let mut stack = vec![(start, self.steps(start).into_iter())];
+
+			while let Some((beam, steps)) = stack.last_mut() {
+				let beam = *beam;
+
+				match steps.find_map(|s| s.beam) {
+					Some(next) => match visits[self.idx(next)] {
+						Visit::New => {
+							visits[self.idx(next)] = Visit::Active;
+							stack.push((next, self.steps(next).into_iter()));
 						}
+						// still on the stack, so it leads back to itself
+						Visit::Active => {
+							cycle.get_or_insert((next.x, next.y));
+						}
+						Visit::Done => {}
+					},
+					None => {
+						visits[self.idx(beam)] = Visit::Done;
+						order.push(beam);
+						stack.pop();
 					}
-					(Symbol::Start | Symbol::Tachion, _) => {
-						*curr = Symbol::Tachion;
-						self.tachion_count[idx] +=
-							self.tachion_count[up_idx].max(1);
-					}
-					_ => {}
 				}
 			}
 		}
+
+		Exploration { order, cycle }
 	}
+}
 
-	fn count_splits(&self) -> u64 {
-		(1..self.height)
-			.map(|y| {
-				(0..self.width)
-					.filter(|x| {
-						let prev = self.cells[(y - 1) * self.width + x];
-						let curr = self.cells[y * self.width + x];
+/// Why the timelines could not be counted.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CountError {
+	Overflow(Overflow),
+	/// A beam can get back to this cell, which makes for endless timelines.
+	Cycle((usize, usize)),
+}
 
-						matches!(
-							(prev, curr),
-							(Symbol::Tachion, Symbol::Splitter)
-						)
-					})
-					.count() as u64
-			})
-			.sum()
+impl From<Overflow> for CountError {
+	fn from(e: Overflow) -> Self {
+		CountError::Overflow(e)
 	}
+}
 
-	fn count_tachions(&self) -> u64 {
-		// count all tachions from the last line
-		self.tachion_count[(self.height - 1) * self.width..]
-			.iter()
-			.sum()
+impl fmt::Display for CountError {
+	fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+		match self {
+			CountError::Overflow(e) => e.fmt(f),
+			CountError::Cycle((x, y)) => {
+				write!(f, "beams run in a cycle through ({x}, {y})")
+			}
+		}
 	}
 }
 
-fn parse_input(input: &str) -> Map {
-	let mut width = 0;
+impl Error for CountError {}
+
+/// The manifold with the number of timelines reaching every cell, counted
+/// with `C`.
+#[derive(Debug, Clone)]
+pub struct Map<C = u64> {
+	cells: Grid<Symbol>,
+	fan_out: Vec<isize>,
+	tachion_count: Grid<C>,
+	timelines: C,
+	/// the count of a single timeline, where every beam starts
+	one: C,
+	splits: u64,
+}
 
-	let cells = input
-		.trim()
-		.lines()
-		.map(|l| {
-			if width != l.len() {
-				assert_eq!(width, 0);
-				width = l.len();
+impl<C: Count> Map<C> {
+	/// Switches to another type for counting, resetting all counts.
+	pub fn with_count<D: Count>(self) -> Map<D> {
+		self.with_count_from(D::one())
+	}
+
+	/// Like [`Map::with_count`] but counting every timeline as `one`, for
+	/// counts chosen at runtime like a [`Modulo`](crate::count::Modulo)
+	/// with its modulus.
+	pub fn with_count_from<D: Count>(self, one: D) -> Map<D> {
+		Map {
+			tachion_count: self.cells.map(|_| D::zero()),
+			cells: self.cells,
+			fan_out: self.fan_out,
+			timelines: D::zero(),
+			one,
+			splits: self.splits,
+		}
+	}
+
+	/// Replaces the column offsets at which a splitter emits new beams, by
+	/// default one to the left and one to the right.
+	pub fn with_fan_out(mut self, fan_out: Vec<isize>) -> Self {
+		self.fan_out = fan_out;
+		self
+	}
+
+	pub fn cells(&self) -> &Grid<Symbol> {
+		&self.cells
+	}
+
+	pub fn fan_out(&self) -> &[isize] {
+		&self.fan_out
+	}
+
+	/// How many timelines pass through each cell, in any direction.
+	pub fn tachion_count(&self) -> &Grid<C> {
+		&self.tachion_count
+	}
+
+	fn beams(&self) -> Beams<'_> {
+		Beams {
+			cells: &self.cells,
+			fan_out: &self.fan_out,
+		}
+	}
+
+	/// Follows every beam from the `S` cells, marking them and counting the
+	/// splitters they hit.
+	///
+	/// Unlike counting the timelines with [`Map::run`] this can't fail.
+	pub fn trace(&mut self) {
+		self.mark();
+	}
+
+	fn mark(&mut self) -> (Vec<Beam>, Exploration) {
+		let beams = self.beams();
+		let starts = beams.starts();
+		let exploration = beams.explore(&starts);
+
+		let mut hit = Vec::new();
+		for beam in &exploration.order {
+			for step in beams.steps(*beam) {
+				hit.extend(step.split.map(|(pos, _)| pos));
+			}
+		}
+		hit.sort_unstable();
+		hit.dedup();
+		self.splits = hit.len() as u64;
+
+		for beam in &exploration.order {
+			let cell = &mut self.cells[(beam.x, beam.y)];
+			if *cell == Symbol::Empty {
+				*cell = Symbol::Tachion;
+			}
+		}
+
+		(starts, exploration)
+	}
+
+	/// Runs the tachion simulation, following every beam from the `S`
+	/// cells and counting the timelines.
+	///
+	/// The beams are always marked completely, an error only means the
+	/// counts are wrong. The counts start from zero on every call.
+	pub fn run(&mut self) -> Result<(), CountError> {
+		self.tachion_count = self.cells.map(|_| C::zero());
+		self.timelines = C::zero();
+
+		let (starts, Exploration { order, cycle }) = self.mark();
+
+		if let Some(pos) = cycle {
+			return Err(CountError::Cycle(pos));
+		}
+
+		// push the counts along the beams, every beam comes after all of
+		// the ones leading to it
+		let beams = Beams {
+			cells: &self.cells,
+			fan_out: &self.fan_out,
+		};
+		let mut count = vec![C::zero(); self.cells.len() * 4];
+		let mut timelines = C::zero();
+		for start in &starts {
+			count[beams.idx(*start)] = self.one.clone();
+		}
+
+		for &beam in order.iter().rev() {
+			let n = &count[beams.idx(beam)];
+			let pos = (beam.x, beam.y);
+			self.tachion_count[pos] = self.tachion_count[pos].try_add(n)?;
+
+			let n = n.clone();
+			for step in beams.steps(beam) {
+				let target = match step.beam {
+					Some(next) => &mut count[beams.idx(next)],
+					None => &mut timelines,
+				};
+				*target = target.try_add(&n)?;
+			}
+		}
+
+		self.timelines = timelines;
+		Ok(())
+	}
+
+	/// The number of distinct splitters hit by a beam.
+	pub fn count_splits(&self) -> u64 {
+		self.splits
+	}
+
+	/// The number of timelines leaving the bottom of the manifold, only
+	/// valid after a successful [`Map::run`].
+	pub fn count_tachions(&self) -> &C {
+		&self.timelines
+	}
+
+	/// The map with all beams, every row followed by the number of
+	/// timelines passing through its cells.
+	pub fn render_text(&self) -> String {
+		let mut s = String::new();
+
+		for (row, counts) in self.cells.rows().zip(self.tachion_count.rows()) {
+			for symbol in row {
+				write!(s, "{symbol}").unwrap();
+			}
+
+			match C::try_sum(counts) {
+				Ok(n) => writeln!(s, "  {n}"),
+				Err(e) => writeln!(s, "  {e}"),
 			}
+			.unwrap();
+		}
+
+		s
+	}
+
+	/// A heatmap of the timeline counts as SVG image, with every other
+	/// symbol than beams drawn on top.
+	///
+	/// The colour intensity of a cell grows with the logarithm of its count,
+	/// `size` is the side length of a cell in pixels.
+	pub fn render_svg(&self, size: usize) -> String {
+		let width = self.cells.width() * size;
+		let height = self.cells.height() * size;
+		let max = self
+			.tachion_count
+			.cells()
+			.iter()
+			.map(C::to_f64)
+			.fold(0.0, f64::max);
+
+		let mut s = format!(
+			"<svg xmlns=\"http://www.w3.org/2000/svg\" width=\"{width}\" \
+			 height=\"{height}\" viewBox=\"0 0 {width} {height}\">\n\
+			 <rect width=\"100%\" height=\"100%\" fill=\"black\"/>\n"
+		);
+
+		for ((x, y), count) in self.tachion_count.iter() {
+			let count = count.to_f64();
+			if count > 0.0 {
+				// shifted by one so a single timeline is still visible
+				let intensity = (count + 1.0).ln() / (max + 1.0).ln();
+				writeln!(
+					s,
+					"<rect x=\"{}\" y=\"{}\" width=\"{size}\" \
+					 height=\"{size}\" fill=\"orange\" \
+					 fill-opacity=\"{intensity:.3}\"/>",
+					x * size,
+					y * size,
+				)
+				.unwrap();
+			}
+		}
+
+		for ((x, y), symbol) in self.cells.iter() {
+			if !matches!(symbol, Symbol::Empty | Symbol::Tachion) {
+				writeln!(
+					s,
+					"<text x=\"{}\" y=\"{}\" font-size=\"{size}\" \
+					 text-anchor=\"middle\" dominant-baseline=\"central\" \
+					 fill=\"white\">{symbol}</text>",
+					x * size + size / 2,
+					y * size + size / 2,
+				)
+				.unwrap();
+			}
+		}
+
+		s += "</svg>\n";
+		s
+	}
 
-			l.as_bytes().iter().map(|b| Symbol::new(b))
+	/// Counts the timelines of beams starting at every `S`, which allows
+	/// looking at individual timelines.
+	pub fn timelines(&self) -> Result<Timelines<'_>, CountError> {
+		let beams = self.beams();
+		let starts = beams.starts();
+		let Exploration { order, cycle } = beams.explore(&starts);
+		if let Some(pos) = cycle {
+			return Err(CountError::Cycle(pos));
+		}
+
+		// every beam comes after all the ones it leads to
+		let mut count = vec![0; self.cells.len() * 4];
+		for beam in order {
+			count[beams.idx(beam)] = u64::try_sum(
+				beams.steps(beam).iter().map(|step| match step.beam {
+					Some(next) => &count[beams.idx(next)],
+					None => &1,
+				}),
+			)?;
+		}
+
+		Ok(Timelines {
+			total: u64::try_sum(starts.iter().map(|&s| &count[beams.idx(s)]))?,
+			beams,
+			starts,
+			count,
 		})
-		.flatten()
-		.collect::<Vec<_>>();
+	}
+}
 
-	Map {
-		width,
-		height: cells.len() / width,
-		tachion_count: vec![0; cells.len()],
-		cells,
+/// A single timeline of a beam from its start until it leaves the bottom.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Path {
+	/// Every cell the beam passes. A beam hitting a splitter continues in the
+	/// cell next to it so the splitters themselves are not part of the path.
+	pub cells: Vec<(usize, usize)>,
+	/// The position of every splitter hit and the fan-out offset the beam
+	/// took.
+	pub splits: Vec<((usize, usize), isize)>,
+}
+
+/// The number of timelines for every beam, see [`Map::timelines`].
+///
+/// Timelines are ordered by their start in row-major order first and then by
+/// their splits in the order they are hit, following the fan-out offsets.
+#[derive(Debug, Clone)]
+pub struct Timelines<'a> {
+	beams: Beams<'a>,
+	starts: Vec<Beam>,
+	count: Vec<u64>,
+	total: u64,
+}
+
+impl Timelines<'_> {
+	/// How many timelines `beam` ends up in.
+	pub fn count(&self, beam: Beam) -> u64 {
+		self.count[self.beams.idx(beam)]
+	}
+
+	pub fn total(&self) -> u64 {
+		self.total
+	}
+
+	/// The `k`th timeline, `None` if there are not that many.
+	pub fn path(&self, mut k: u64) -> Option<Path> {
+		let &start = self.starts.iter().find(|&&beam| {
+			let found = k < self.count(beam);
+			if !found {
+				k -= self.count(beam);
+			}
+			found
+		})?;
+
+		let mut path = Path {
+			cells: vec![(start.x, start.y)],
+			splits: Vec::new(),
+		};
+
+		let mut beam = start;
+		loop {
+			let step = self.beams.steps(beam).into_iter().find(|step| {
+				let count = step.beam.map_or(1, |next| self.count(next));
+				let found = k < count;
+				if !found {
+					k -= count;
+				}
+				found
+			})?;
+
+			path.splits.extend(step.split);
+			match step.beam {
+				Some(next) => {
+					path.cells.push((next.x, next.y));
+					beam = next;
+				}
+				None => return Some(path),
+			}
+		}
+	}
+
+	/// All timelines in order, each one is only built when it's needed.
+	pub fn paths(&self) -> impl Iterator<Item = Path> + '_ {
+		(0..self.total()).map_while(|k| self.path(k))
+	}
+
+	/// Picks one timeline uniformly at random.
+	///
+	/// `random` has to return uniformly distributed numbers, it might get
+	/// called more than once.
+	pub fn sample(&self, mut random: impl FnMut() -> u64) -> Option<Path> {
+		let total = self.total();
+		if total == 0 {
+			return None;
+		}
+
+		// reject the last incomplete multiple of `total` to avoid a bias
+		let limit = u64::MAX - u64::MAX % total;
+		loop {
+			let r = random();
+			if r < limit {
+				return self.path(r % total);
+			}
+		}
 	}
 }
 
-fn part1() -> u64 {
-	let mut map = parse_input(INPUT);
-	map.run();
+impl<C> fmt::Display for Map<C> {
+	fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+		self.cells.fmt(f)
+	}
+}
+
+pub fn parse_input(input: &str) -> Result<Map, ParseError> {
+	let cells = Grid::parse(
+		input,
+		"`.`, `S`, `^`, `|`, `/`, `\\` or `#`",
+		Symbol::new,
+	)?;
+
+	Ok(Map {
+		tachion_count: cells.map(|_| 0),
+		cells,
+		fan_out: vec![-1, 1],
+		timelines: 0,
+		one: 1,
+		splits: 0,
+	})
+}
+
+pub fn part1(mut map: Map) -> u64 {
+	map.trace();
 	map.count_splits()
 }
 
-fn part2() -> u64 {
-	let mut map = parse_input(INPUT);
-	map.run();
-	map.count_tachions()
+pub fn part2(mut map: Map) -> Result<u64, CountError> {
+	map.run()?;
+	Ok(*map.count_tachions())
 }
 
-fn main() {
-	let p1 = part1();
-	println!("Part 1: {p1}");
-	assert_eq!(p1, 1635);
+pub struct Day7;
+
+impl Solution for Day7 {
+	fn day(&self) -> u32 {
+		7
+	}
+
+	fn input(&self) -> &'static str {
+		INPUT
+	}
+
+	fn part1(&self, input: &str) -> Result<u64, SolveError> {
+		Ok(part1(parse_input(input)?))
+	}
+
+	fn part2(&self, input: &str) -> Result<u64, SolveError> {
+		part2(parse_input(input)?).map_err(SolveError::unsolvable)
+	}
 
-	let p2 = part2();
-	println!("Part 2: {p2}");
-	assert_eq!(p2, 58097428661390);
+	fn bench(
+		&self,
+		input: &str,
+		runs: usize,
+		parts: &[Part],
+	) -> Result<DayTimings, ParseError> {
+		bench::phases(input, runs, parts, parse_input, part1, part2)
+	}
 }
 
-#[test]
-fn test_p2() {
-	let mut map = parse_input(
-		"\
+#[cfg(test)]
+const EXAMPLE: &str = "\
 .......S.......
 ...............
 .......^.......
@@ -160,8 +700,248 @@ fn test_p2() {
 ..^...^.....^..
 ...............
 .^.^.^.^.^...^.
-...............",
+...............";
+
+#[test]
+fn test_p2() {
+	let mut map = parse_input(EXAMPLE).unwrap();
+	map.run().unwrap();
+	assert_eq!(*map.count_tachions(), 40);
+}
+
+#[test]
+fn test_parse_error() {
+	assert_eq!(
+		parse_input("..S..\n..v..").unwrap_err(),
+		ParseError::new(2, 3, "v", "`.`, `S`, `^`, `|`, `/`, `\\` or `#`")
+	);
+}
+
+#[test]
+fn test_timelines() {
+	let map = parse_input(EXAMPLE).unwrap();
+	let timelines = map.timelines().unwrap();
+	assert_eq!(timelines.total(), 40);
+
+	let first = timelines.path(0).unwrap();
+	assert_eq!(first.cells.len(), 16);
+	assert_eq!(first.cells[0], (7, 0));
+	assert_eq!(first.cells[15], (0, 15));
+	assert!(first.splits.iter().all(|(_, d)| *d == -1));
+	assert_eq!(first.splits[0], ((7, 2), -1));
+
+	let last = timelines.path(39).unwrap();
+	assert_eq!(last.cells[15], (14, 15));
+	assert!(last.splits.iter().all(|(_, d)| *d == 1));
+	assert_eq!(timelines.path(40), None);
+
+	let paths = timelines.paths().collect::<Vec<_>>();
+	assert_eq!(paths.len(), 40);
+	assert!(paths.windows(2).all(|w| w[0] != w[1]));
+
+	for path in &paths {
+		// one cell per row, moving at most one column at a splitter
+		for (i, w) in path.cells.windows(2).enumerate() {
+			let ((x0, y0), (x1, y1)) = (w[0], w[1]);
+			assert_eq!((y0, y1), (i, i + 1));
+			assert!(x0.abs_diff(x1) <= 1);
+		}
+		for &((x, y), d) in &path.splits {
+			assert_eq!(map.cells()[(x, y)], Symbol::Splitter);
+			assert_eq!(path.cells[y], (x.wrapping_add_signed(d), y));
+		}
+	}
+
+	let mut state = 0x9e37_79b9_7f4a_7c15u64;
+	let mut seen = [0; 40];
+	for _ in 0..400 {
+		let path = timelines
+			.sample(|| {
+				state ^= state << 13;
+				state ^= state >> 7;
+				state ^= state << 17;
+				state
+			})
+			.unwrap();
+		let k = paths.iter().position(|p| *p == path).unwrap();
+		seen[k] += 1;
+	}
+	assert!(seen.iter().all(|&n| n > 0));
+
+	// the same total as the simulation
+	let mut map = map.clone();
+	map.run().unwrap();
+	assert_eq!(map.timelines().unwrap().total(), *map.count_tachions());
+}
+
+/// A full pyramid of `levels` rows of splitters, which has `2^levels`
+/// timelines.
+#[cfg(test)]
+fn pyramid(levels: usize) -> Map {
+	let width = 2 * levels + 3;
+	let centre = levels + 1;
+	let mut rows =
+		vec![Symbol::Empty.to_string().repeat(width); 2 * levels + 2];
+	rows[0].replace_range(centre..=centre, "S");
+
+	for level in 1..=levels {
+		for i in 0..level {
+			let x = centre + 2 * i + 1 - level;
+			rows[2 * level].replace_range(x..=x, "^");
+		}
+	}
+
+	parse_input(&rows.join("\n")).unwrap()
+}
+
+#[test]
+fn test_counts() {
+	use crate::{bigint::BigUint, count::Modulo};
+
+	let example = parse_input(EXAMPLE).unwrap();
+	let mut map = example.clone().with_count::<BigUint>();
+	map.run().unwrap();
+	assert_eq!(map.count_tachions().to_string(), "40");
+
+	let mut map = example.with_count_from(Modulo::new(1, 7));
+	map.run().unwrap();
+	assert_eq!(map.count_tachions().value(), 5);
+
+	// too many for a u64, but the beams are still correct
+	let mut map = pyramid(100);
+	assert_eq!(
+		map.run(),
+		Err(CountError::Overflow(Overflow { name: "u64" }))
+	);
+	assert_eq!(map.count_splits(), 100 * 101 / 2);
+	assert!(map.timelines().is_err());
+
+	let mut map = pyramid(100).with_count::<u128>();
+	map.run().unwrap();
+	assert_eq!(*map.count_tachions(), 1 << 100);
+	let rendered = map.render_text();
+	map.run().unwrap();
+	assert_eq!(*map.count_tachions(), 1 << 100);
+	assert_eq!(map.render_text(), rendered);
+
+	// the prime is only known at runtime
+	for p in "1000000007 998244353 7"
+		.split(' ')
+		.map(|p| p.parse().unwrap())
+	{
+		let mut map = pyramid(100).with_count_from(Modulo::new(1, p));
+		map.run().unwrap();
+		let count = map.count_tachions();
+		assert_eq!(count.value() as u128, (1 << 100) % p as u128);
+		assert_eq!(count.modulus(), Some(p));
+	}
+
+	let mut map = pyramid(130).with_count::<u128>();
+	assert!(map.run().is_err());
+
+	let mut map = pyramid(130).with_count::<BigUint>();
+	map.run().unwrap();
+	assert_eq!(
+		map.count_tachions().to_string(),
+		"1361129467683753853853498429727072845824"
+	);
+}
+
+#[test]
+fn test_elements() {
+	let run = |input: &str| {
+		let mut map = parse_input(input).unwrap();
+		map.run()
+			.map(|()| (*map.count_tachions(), map.count_splits()))
+	};
+
+	// mirrors send the beam around into the side of the splitter
+	let mut map = parse_input(
+		"\
+.S....
+.\\...\\
+.#....
+...^./
+......",
+	)
+	.unwrap();
+	map.run().unwrap();
+	assert_eq!(
+		map.to_string(),
+		"\
+.S....
+.\\|||\\
+.#...|
+..|^|/
+..|.|."
+	);
+	assert_eq!((*map.count_tachions(), map.count_splits()), (2, 1));
+	assert_eq!(map.tachion_count()[(2, 1)], 1);
+	assert_eq!(map.tachion_count()[(4, 3)], 2);
+
+	let timelines = map.timelines().unwrap();
+	let path = timelines.path(0).unwrap();
+	assert_eq!(
+		path.cells,
+		[(1, 0), (1, 1), (2, 1), (3, 1), (4, 1), (5, 1), (5, 2)]
+			.into_iter()
+			.chain([(5, 3), (4, 3), (2, 3), (2, 4)])
+			.collect::<Vec<_>>()
+	);
+	assert_eq!(path.splits, [((3, 3), -1)]);
+	assert_eq!(timelines.path(1).unwrap().splits, [((3, 3), 1)]);
+
+	// absorbed and lost beams end no timeline
+	assert_eq!(run(".S.\n.#.\n..."), Ok((0, 0)));
+	assert_eq!(run("S..\n^..\n..."), Ok((1, 1)));
+
+	// multiple starts add up
+	assert_eq!(run(".S.S.\n.^...\n....."), Ok((3, 1)));
+
+	// three beams per splitter
+	let mut map = pyramid(3).with_fan_out(vec![-1, 0, 1]);
+	map.run().unwrap();
+	assert_eq!(*map.count_tachions(), 17);
+	assert_eq!(map.timelines().unwrap().total(), 17);
+
+	// going round and round between the mirrors
+	assert_eq!(run("/.\\\nS..\n\\./\n..."), Err(CountError::Cycle((0, 1))));
+	let cycle = "/.\\\nS..\n\\./\n...";
+	assert_eq!(Day7.part1(cycle).unwrap(), 0);
+	assert_eq!(
+		Day7.part2(cycle).unwrap_err().to_string(),
+		"no answer: beams run in a cycle through (0, 1)"
+	);
+}
+
+#[test]
+fn test_render() {
+	let mut map = parse_input(EXAMPLE).unwrap();
+	map.run().unwrap();
+
+	let text = map.render_text();
+	let lines = text.lines().collect::<Vec<_>>();
+	assert_eq!(lines.len(), 16);
+	assert_eq!(lines[0], ".......S.......  1");
+	assert_eq!(lines[2], "......|^|......  2");
+	assert_eq!(lines[15], "|.|.|.|.|.|||.|  40");
+
+	let svg = map.render_svg(10);
+	assert!(svg.starts_with(
+		"<svg xmlns=\"http://www.w3.org/2000/svg\" \
+	                         width=\"150\" height=\"160\""
+	));
+	assert!(svg.ends_with("</svg>\n"));
+
+	let beams = map.tachion_count().cells().iter().filter(|&&n| n > 0);
+	assert_eq!(svg.matches("fill=\"orange\"").count(), beams.count());
+	// only the start and the splitters are drawn as text
+	assert_eq!(svg.matches("<text").count(), 1 + 22);
+	// the cells with the most timelines get the full colour
+	let counts = map.tachion_count().cells();
+	let max = counts.iter().max().unwrap();
+	assert_eq!(
+		svg.matches("fill-opacity=\"1.000\"").count(),
+		counts.iter().filter(|&n| n == max).count()
 	);
-	map.run();
-	assert_eq!(map.count_tachions(), 40);
 }
@@ -0,0 +1,343 @@
+use std::{
+	fmt,
+	ops::{Index, IndexMut},
+};
+
+use crate::parse::ParseError;
+
+/// A two dimensional grid stored row-major in a single `Vec`.
+///
+/// `x` is the column and `y` the row, `(0, 0)` being the top left.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Grid<T> {
+	cells: Vec<T>,
+	width: usize,
+	height: usize,
+}
+
+impl<T> Grid<T> {
+	/// Creates a grid from its row-major cells.
+	///
+	/// ## Panics
+	/// If `cells` can't be split into rows of `width`.
+	pub fn from_vec(width: usize, cells: Vec<T>) -> Self {
+		assert!(
+			width > 0 && cells.len().is_multiple_of(width),
+			"invalid grid width"
+		);
+
+		Self {
+			height: cells.len() / width,
+			width,
+			cells,
+		}
+	}
+
+	/// Creates a grid by calling `f` for every position.
+	pub fn from_fn(
+		width: usize,
+		height: usize,
+		mut f: impl FnMut(usize, usize) -> T,
+	) -> Self {
+		let cells = (0..height)
+			.flat_map(|y| (0..width).map(move |x| (x, y)))
+			.map(|(x, y)| f(x, y))
+			.collect();
+
+		Self::from_vec(width, cells)
+	}
+
+	/// Parses a grid where every char is one cell.
+	///
+	/// `f` maps a char to a cell, returning `None` if the char is invalid in
+	/// which case `expected` is used in the error. Every line needs to have
+	/// the same width.
+	pub fn parse(
+		input: &str,
+		expected: &str,
+		mut f: impl FnMut(char) -> Option<T>,
+	) -> Result<Self, ParseError> {
+		let mut width = 0;
+		let mut cells = Vec::new();
+
+		for l in input.trim().lines() {
+			let len = l.chars().count();
+			if width == 0 {
+				width = len;
+			} else if width != len {
+				return Err(ParseError::at(
+					input,
+					l,
+					format!("a line of width {width}"),
+				));
+			}
+
+			for (i, c) in l.char_indices() {
+				let cell = f(c).ok_or_else(|| {
+					ParseError::at_char(input, &l[i..], expected)
+				})?;
+				cells.push(cell);
+			}
+		}
+
+		if width == 0 {
+			return Err(ParseError::at(input, input, "at least one row"));
+		}
+
+		Ok(Self::from_vec(width, cells))
+	}
+
+	pub fn width(&self) -> usize {
+		self.width
+	}
+
+	pub fn height(&self) -> usize {
+		self.height
+	}
+
+	pub fn len(&self) -> usize {
+		self.cells.len()
+	}
+
+	pub fn is_empty(&self) -> bool {
+		self.cells.is_empty()
+	}
+
+	/// Returns the index into the row-major cells if `(x, y)` is inside the
+	/// grid.
+	pub fn idx(&self, x: usize, y: usize) -> Option<usize> {
+		(x < self.width && y < self.height).then(|| y * self.width + x)
+	}
+
+	/// The inverse of [`Grid::idx`].
+	pub fn pos(&self, idx: usize) -> (usize, usize) {
+		(idx % self.width, idx / self.width)
+	}
+
+	pub fn get(&self, x: usize, y: usize) -> Option<&T> {
+		self.idx(x, y).map(|i| &self.cells[i])
+	}
+
+	pub fn get_mut(&mut self, x: usize, y: usize) -> Option<&mut T> {
+		self.idx(x, y).map(|i| &mut self.cells[i])
+	}
+
+	/// All cells in row-major order.
+	pub fn cells(&self) -> &[T] {
+		&self.cells
+	}
+
+	pub fn cells_mut(&mut self) -> &mut [T] {
+		&mut self.cells
+	}
+
+	/// All positions in row-major order.
+	pub fn positions(&self) -> impl Iterator<Item = (usize, usize)> + use<T> {
+		let width = self.width;
+		(0..self.cells.len()).map(move |i| (i % width, i / width))
+	}
+
+	/// All cells with their position in row-major order.
+	pub fn iter(&self) -> impl Iterator<Item = ((usize, usize), &T)> {
+		self.positions().zip(&self.cells)
+	}
+
+	pub fn row(&self, y: usize) -> &[T] {
+		&self.cells[y * self.width..][..self.width]
+	}
+
+	pub fn rows(&self) -> impl DoubleEndedIterator<Item = &[T]> {
+		self.cells.chunks_exact(self.width)
+	}
+
+	pub fn column(&self, x: usize) -> impl DoubleEndedIterator<Item = &T> {
+		assert!(x < self.width, "column out of bounds");
+		self.cells[x..].iter().step_by(self.width)
+	}
+
+	pub fn columns(
+		&self,
+	) -> impl Iterator<Item = impl DoubleEndedIterator<Item = &T>> {
+		(0..self.width).map(|x| self.column(x))
+	}
+
+	/// The positions of the horizontal and vertical neighbours of `(x, y)`
+	/// which are inside the grid.
+	pub fn neighbours4(
+		&self,
+		x: usize,
+		y: usize,
+	) -> impl Iterator<Item = (usize, usize)> + use<T> {
+		#[rustfmt::skip]
+		const DISPLACEMENT: &[(isize, isize); 4] = &[
+			         (0, -1),
+			(-1, 0),          (1, 0),
+			         (0, 1),
+		];
+
+		self.offset_positions(x, y, DISPLACEMENT)
+	}
+
+	/// The positions of all neighbours of `(x, y)` including the diagonal
+	/// ones which are inside the grid.
+	pub fn neighbours8(
+		&self,
+		x: usize,
+		y: usize,
+	) -> impl Iterator<Item = (usize, usize)> + use<T> {
+		#[rustfmt::skip]
+		const DISPLACEMENT: &[(isize, isize); 8] = &[
+			(-1, -1), (0, -1), (1, -1),
+			(-1, 0),           (1, 0),
+			(-1, 1),  (0, 1),  (1, 1),
+		];
+
+		self.offset_positions(x, y, DISPLACEMENT)
+	}
+
+	/// The positions `(x + dx, y + dy)` for every offset which are inside the
+	/// grid.
+	pub fn offset_positions<'a>(
+		&self,
+		x: usize,
+		y: usize,
+		offsets: &'a [(isize, isize)],
+	) -> impl Iterator<Item = (usize, usize)> + use<'a, T> {
+		let (width, height) = (self.width, self.height);
+
+		offsets.iter().filter_map(move |(dx, dy)| {
+			let nx = x.checked_add_signed(*dx).filter(|&nx| nx < width)?;
+			let ny = y.checked_add_signed(*dy).filter(|&ny| ny < height)?;
+			Some((nx, ny))
+		})
+	}
+
+	/// Like [`Grid::offset_positions`] but wraps around the edges as if the
+	/// grid was a torus.
+	///
+	/// Offsets larger than the grid can wrap onto the same cell more than
+	/// once.
+	pub fn wrapping_offset_positions<'a>(
+		&self,
+		x: usize,
+		y: usize,
+		offsets: &'a [(isize, isize)],
+	) -> impl Iterator<Item = (usize, usize)> + use<'a, T> {
+		let (width, height) = (self.width as isize, self.height as isize);
+
+		offsets.iter().map(move |(dx, dy)| {
+			let nx = (x as isize + dx).rem_euclid(width);
+			let ny = (y as isize + dy).rem_euclid(height);
+			(nx as usize, ny as usize)
+		})
+	}
+
+	/// Creates a new grid of the same size by mapping every cell.
+	pub fn map<U>(&self, f: impl FnMut(&T) -> U) -> Grid<U> {
+		Grid::from_vec(self.width, self.cells.iter().map(f).collect())
+	}
+}
+
+impl<T: Clone> Grid<T> {
+	pub fn new(width: usize, height: usize, fill: T) -> Self {
+		Self::from_vec(width, vec![fill; width * height])
+	}
+}
+
+impl<T> Index<(usize, usize)> for Grid<T> {
+	type Output = T;
+
+	fn index(&self, (x, y): (usize, usize)) -> &T {
+		self.get(x, y).expect("position out of bounds")
+	}
+}
+
+impl<T> IndexMut<(usize, usize)> for Grid<T> {
+	fn index_mut(&mut self, (x, y): (usize, usize)) -> &mut T {
+		self.get_mut(x, y).expect("position out of bounds")
+	}
+}
+
+/// Writes every row on its own line without separators between cells.
+impl<T: fmt::Display> fmt::Display for Grid<T> {
+	fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+		for (y, row) in self.rows().enumerate() {
+			if y > 0 {
+				f.write_str("\n")?;
+			}
+
+			for cell in row {
+				cell.fmt(f)?;
+			}
+		}
+
+		Ok(())
+	}
+}
+
+#[cfg(test)]
+fn digits() -> Grid<u32> {
+	Grid::parse("123\n456\n", "a digit", |c| c.to_digit(10)).unwrap()
+}
+
+#[test]
+fn test_parse() {
+	let grid = digits();
+	assert_eq!((grid.width(), grid.height()), (3, 2));
+	assert_eq!(grid.get(2, 1), Some(&6));
+	assert_eq!(grid.get(3, 0), None);
+	assert_eq!(grid.to_string(), "123\n456");
+
+	assert_eq!(
+		Grid::parse("12\n3x", "a digit", |c| c.to_digit(10)),
+		Err(ParseError::new(2, 2, "x", "a digit"))
+	);
+	assert_eq!(
+		Grid::parse("12\n345", "a digit", |c| c.to_digit(10)),
+		Err(ParseError::new(2, 1, "345", "a line of width 2"))
+	);
+}
+
+#[test]
+fn test_neighbours() {
+	let grid = digits();
+
+	let mut n = grid.neighbours4(0, 0).collect::<Vec<_>>();
+	n.sort_unstable();
+	assert_eq!(n, [(0, 1), (1, 0)]);
+
+	let mut n = grid.neighbours8(1, 1).collect::<Vec<_>>();
+	n.sort_unstable();
+	assert_eq!(n, [(0, 0), (0, 1), (1, 0), (2, 0), (2, 1)]);
+}
+
+#[test]
+fn test_wrapping() {
+	let grid = digits();
+
+	let offsets = [(-1, -1), (1, 1), (3, 0)];
+	assert_eq!(
+		grid.offset_positions(0, 0, &offsets).collect::<Vec<_>>(),
+		[(1, 1)]
+	);
+	assert_eq!(
+		grid.wrapping_offset_positions(0, 0, &offsets)
+			.collect::<Vec<_>>(),
+		[(2, 1), (1, 1), (0, 0)]
+	);
+}
+
+#[test]
+fn test_rows_columns() {
+	let grid = digits();
+
+	assert_eq!(grid.rows().collect::<Vec<_>>(), [[1, 2, 3], [4, 5, 6]]);
+	assert_eq!(grid.row(1), [4, 5, 6]);
+	assert_eq!(grid.column(1).copied().collect::<Vec<_>>(), [2, 5]);
+	assert_eq!(
+		grid.columns()
+			.map(|c| c.copied().sum::<u32>())
+			.collect::<Vec<_>>(),
+		[5, 7, 9]
+	);
+}
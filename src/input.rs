@@ -0,0 +1,47 @@
+use std::{
+	borrow::Cow,
+	fs,
+	io::{self, Read},
+	path::PathBuf,
+};
+
+use crate::solution::Solution;
+
+/// Where the puzzle input of a day gets read from.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub enum InputSource {
+	/// The input baked into the binary.
+	#[default]
+	Default,
+	Stdin,
+	File(PathBuf),
+}
+
+impl InputSource {
+	/// Parses a command line value, `-` means stdin.
+	pub fn from_arg(arg: &str) -> Self {
+		match arg {
+			"-" => InputSource::Stdin,
+			path => InputSource::File(path.into()),
+		}
+	}
+
+	pub fn is_default(&self) -> bool {
+		matches!(self, InputSource::Default)
+	}
+
+	pub fn load(
+		&self,
+		solution: &dyn Solution,
+	) -> io::Result<Cow<'static, str>> {
+		match self {
+			InputSource::Default => Ok(Cow::Borrowed(solution.input())),
+			InputSource::Stdin => {
+				let mut s = String::new();
+				io::stdin().read_to_string(&mut s)?;
+				Ok(Cow::Owned(s))
+			}
+			InputSource::File(path) => fs::read_to_string(path).map(Cow::Owned),
+		}
+	}
+}
@@ -0,0 +1,193 @@
+use std::fmt;
+
+use crate::bigint::BigUint;
+
+/// An unsigned integer with the arithmetic needed for ids.
+///
+/// All operations panic on overflow and underflow.
+pub trait Int: Clone + Ord + fmt::Debug + fmt::Display {
+	/// The name of the type, for messages.
+	const NAME: &'static str;
+
+	fn from_u64(n: u64) -> Self;
+
+	fn to_big(&self) -> BigUint;
+
+	/// Parses a decimal number, `None` if it's invalid or too large.
+	fn parse(s: &str) -> Option<Self>;
+
+	fn add(&self, other: &Self) -> Self;
+
+	fn sub(&self, other: &Self) -> Self;
+
+	fn mul(&self, other: &Self) -> Self;
+
+	fn div(&self, other: &Self) -> Self;
+
+	fn rem(&self, other: &Self) -> Self;
+
+	fn is_zero(&self) -> bool {
+		*self == Self::from_u64(0)
+	}
+
+	fn pow(&self, exp: u32) -> Self {
+		(0..exp).fold(Self::from_u64(1), |n, _| n.mul(self))
+	}
+
+	/// The number of digits in `base`, zero having one digit.
+	fn digits(&self, base: &Self) -> u32 {
+		let mut digits = 1;
+		let mut n = self.div(base);
+
+		while !n.is_zero() {
+			n = n.div(base);
+			digits += 1;
+		}
+
+		digits
+	}
+}
+
+macro_rules! impl_int {
+	($t:ty) => {
+		impl Int for $t {
+			const NAME: &'static str = stringify!($t);
+
+			fn from_u64(n: u64) -> Self {
+				n as $t
+			}
+
+			fn to_big(&self) -> BigUint {
+				BigUint::from(*self)
+			}
+
+			fn parse(s: &str) -> Option<Self> {
+				s.parse().ok()
+			}
+
+			fn add(&self, other: &Self) -> Self {
+				self.checked_add(*other).expect("integer overflow")
+			}
+
+			fn sub(&self, other: &Self) -> Self {
+				self.checked_sub(*other).expect("integer underflow")
+			}
+
+			fn mul(&self, other: &Self) -> Self {
+				self.checked_mul(*other).expect("integer overflow")
+			}
+
+			fn div(&self, other: &Self) -> Self {
+				self / other
+			}
+
+			fn rem(&self, other: &Self) -> Self {
+				self % other
+			}
+		}
+	};
+}
+
+impl_int!(u64);
+impl_int!(u128);
+
+impl Int for BigUint {
+	const NAME: &'static str = "BigUint";
+
+	fn from_u64(n: u64) -> Self {
+		BigUint::from(n)
+	}
+
+	fn to_big(&self) -> BigUint {
+		self.clone()
+	}
+
+	fn parse(s: &str) -> Option<Self> {
+		BigUint::parse(s)
+	}
+
+	fn add(&self, other: &Self) -> Self {
+		self + other
+	}
+
+	fn sub(&self, other: &Self) -> Self {
+		self - other
+	}
+
+	fn mul(&self, other: &Self) -> Self {
+		self * other
+	}
+
+	fn div(&self, other: &Self) -> Self {
+		self / other
+	}
+
+	fn rem(&self, other: &Self) -> Self {
+		self % other
+	}
+}
+
+/// An integer to store ids in, with a wider one to calculate with them.
+///
+/// Powers of the base and sums of many ids don't fit into the id type
+/// itself.
+pub trait Id: Int {
+	type Wide: Int;
+
+	fn widen(&self) -> Self::Wide;
+
+	/// `None` if the value does not fit.
+	fn narrow(wide: &Self::Wide) -> Option<Self>;
+}
+
+impl Id for u64 {
+	type Wide = u128;
+
+	fn widen(&self) -> u128 {
+		*self as u128
+	}
+
+	fn narrow(wide: &u128) -> Option<Self> {
+		(*wide).try_into().ok()
+	}
+}
+
+impl Id for u128 {
+	type Wide = BigUint;
+
+	fn widen(&self) -> BigUint {
+		BigUint::from(*self)
+	}
+
+	fn narrow(wide: &BigUint) -> Option<Self> {
+		wide.to_u128()
+	}
+}
+
+impl Id for BigUint {
+	type Wide = BigUint;
+
+	fn widen(&self) -> BigUint {
+		self.clone()
+	}
+
+	fn narrow(wide: &BigUint) -> Option<Self> {
+		Some(wide.clone())
+	}
+}
+
+#[test]
+fn test_int() {
+	assert_eq!(Int::pow(&10u64, 3), 1000);
+	assert_eq!(Int::pow(&BigUint::from(10u64), 0), BigUint::from(1u64));
+	assert_eq!(0u64.digits(&10), 1);
+	assert_eq!(999u64.digits(&10), 3);
+	assert_eq!(0xffu64.digits(&16), 2);
+	assert_eq!(0x100u64.digits(&2), 9);
+	assert_eq!(u128::MAX.digits(&10), 39);
+
+	assert_eq!(u64::narrow(&u64::MAX.widen()), Some(u64::MAX));
+	assert_eq!(u64::narrow(&(1 << 64)), None);
+	assert_eq!(u128::narrow(&u128::MAX.widen()), Some(u128::MAX));
+	assert_eq!(<u64 as Int>::parse("18446744073709551616"), None);
+}
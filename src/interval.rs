@@ -0,0 +1,246 @@
+use std::{
+	iter::Sum,
+	ops::{Range, Sub},
+};
+
+/// A set of values stored as half-open ranges.
+///
+/// The ranges are always sorted, non-empty, disjoint and never touch each
+/// other, so two sets containing the same values are always equal.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct IntervalSet<T> {
+	ranges: Vec<Range<T>>,
+}
+
+impl<T> Default for IntervalSet<T> {
+	fn default() -> Self {
+		Self { ranges: Vec::new() }
+	}
+}
+
+impl<T: Ord + Copy> IntervalSet<T> {
+	pub fn new() -> Self {
+		Self::default()
+	}
+
+	pub fn from_range(range: Range<T>) -> Self {
+		let mut set = Self::new();
+		set.insert(range);
+		set
+	}
+
+	/// The normalized ranges, sorted ascending.
+	pub fn ranges(&self) -> &[Range<T>] {
+		&self.ranges
+	}
+
+	pub fn is_empty(&self) -> bool {
+		self.ranges.is_empty()
+	}
+
+	/// Returns true if `value` is part of any range, in O(log n).
+	pub fn contains(&self, value: &T) -> bool {
+		let i = self.ranges.partition_point(|r| r.end <= *value);
+		self.ranges.get(i).is_some_and(|r| r.start <= *value)
+	}
+
+	pub fn insert(&mut self, range: Range<T>) {
+		if range.is_empty() {
+			return;
+		}
+
+		// all ranges which overlap or touch the new one get merged into it
+		let first = self.ranges.partition_point(|r| r.end < range.start);
+		let last = self.ranges.partition_point(|r| r.start <= range.end);
+
+		let mut merged = range;
+		if first < last {
+			merged.start = merged.start.min(self.ranges[first].start);
+			merged.end = merged.end.max(self.ranges[last - 1].end);
+		}
+
+		self.ranges.splice(first..last, [merged]);
+	}
+
+	pub fn remove(&mut self, range: Range<T>) {
+		if range.is_empty() {
+			return;
+		}
+
+		let first = self.ranges.partition_point(|r| r.end <= range.start);
+		let last = self.ranges.partition_point(|r| r.start < range.end);
+		if first >= last {
+			return;
+		}
+
+		// the first and last range might stick out on either side
+		let before = self.ranges[first].start..range.start;
+		let after = range.end..self.ranges[last - 1].end;
+
+		self.ranges.splice(
+			first..last,
+			[before, after].into_iter().filter(|r| !r.is_empty()),
+		);
+	}
+
+	pub fn union(&self, other: &Self) -> Self {
+		let (mut set, other) = if self.ranges.len() >= other.ranges.len() {
+			(self.clone(), other)
+		} else {
+			(other.clone(), self)
+		};
+
+		for r in &other.ranges {
+			set.insert(r.clone());
+		}
+
+		set
+	}
+
+	pub fn intersection(&self, other: &Self) -> Self {
+		let (a, b) = (&self.ranges, &other.ranges);
+		let mut ranges = Vec::new();
+		let (mut i, mut j) = (0, 0);
+
+		while i < a.len() && j < b.len() {
+			let start = a[i].start.max(b[j].start);
+			let end = a[i].end.min(b[j].end);
+			if start < end {
+				ranges.push(start..end);
+			}
+
+			if a[i].end < b[j].end {
+				i += 1;
+			} else {
+				j += 1;
+			}
+		}
+
+		Self { ranges }
+	}
+
+	/// All values in `self` which are not in `other`.
+	pub fn difference(&self, other: &Self) -> Self {
+		let b = &other.ranges;
+		let mut ranges = Vec::new();
+		let mut j = 0;
+
+		for r in &self.ranges {
+			// skip everything that ends before this range
+			while j < b.len() && b[j].end <= r.start {
+				j += 1;
+			}
+
+			let mut start = r.start;
+			for cut in b[j..].iter().take_while(|c| c.start < r.end) {
+				if start < cut.start {
+					ranges.push(start..cut.start);
+				}
+				start = start.max(cut.end);
+			}
+
+			if start < r.end {
+				ranges.push(start..r.end);
+			}
+		}
+
+		Self { ranges }
+	}
+
+	/// All values inside `bound` which are not in `self`.
+	pub fn complement(&self, bound: Range<T>) -> Self {
+		Self::from_range(bound).difference(self)
+	}
+}
+
+impl<T> IntervalSet<T>
+where
+	T: Ord + Copy + Sub<Output = T> + Sum<T>,
+{
+	/// The amount of values in the set.
+	pub fn size(&self) -> T {
+		self.ranges.iter().map(|r| r.end - r.start).sum()
+	}
+}
+
+impl<T: Ord + Copy> FromIterator<Range<T>> for IntervalSet<T> {
+	fn from_iter<I: IntoIterator<Item = Range<T>>>(iter: I) -> Self {
+		let mut ranges = iter
+			.into_iter()
+			.filter(|r| !r.is_empty())
+			.collect::<Vec<_>>();
+		ranges.sort_unstable_by_key(|r| r.start);
+
+		let mut merged: Vec<Range<T>> = Vec::with_capacity(ranges.len());
+		for r in ranges {
+			match merged.last_mut() {
+				Some(last) if r.start <= last.end => {
+					last.end = last.end.max(r.end);
+				}
+				_ => merged.push(r),
+			}
+		}
+
+		Self { ranges: merged }
+	}
+}
+
+#[cfg(test)]
+fn set(ranges: &[Range<u64>]) -> IntervalSet<u64> {
+	ranges.iter().cloned().collect()
+}
+
+#[test]
+fn test_normalize() {
+	assert_eq!(
+		set(&[5..8, 0..3, 3..4, 7..10, 12..12]).ranges(),
+		[0..4, 5..10]
+	);
+
+	let mut s = IntervalSet::new();
+	s.insert(10..20);
+	s.insert(0..5);
+	s.insert(5..7);
+	s.insert(15..30);
+	s.insert(40..40);
+	assert_eq!(s, set(&[0..7, 10..30]));
+}
+
+#[test]
+fn test_remove() {
+	let mut s = set(&[0..10, 20..30]);
+	s.remove(5..25);
+	assert_eq!(s, set(&[0..5, 25..30]));
+
+	s.remove(0..1);
+	s.remove(26..27);
+	assert_eq!(s, set(&[1..5, 25..26, 27..30]));
+
+	s.remove(0..100);
+	assert!(s.is_empty());
+}
+
+#[test]
+fn test_contains() {
+	let s = set(&[3..6, 10..15]);
+
+	assert!(!s.contains(&2));
+	assert!(s.contains(&3));
+	assert!(s.contains(&5));
+	assert!(!s.contains(&6));
+	assert!(s.contains(&14));
+	assert!(!s.contains(&15));
+}
+
+#[test]
+fn test_set_operations() {
+	let a = set(&[0..10, 20..30]);
+	let b = set(&[5..25, 28..40]);
+
+	assert_eq!(a.union(&b), IntervalSet::from_range(0..40));
+	assert_eq!(a.intersection(&b), set(&[5..10, 20..25, 28..30]));
+	assert_eq!(a.difference(&b), set(&[0..5, 25..28]));
+	assert_eq!(b.difference(&a), set(&[10..20, 30..40]));
+	assert_eq!(a.complement(0..50), set(&[10..20, 30..50]));
+	assert_eq!(a.size(), 20);
+}
@@ -0,0 +1,35 @@
+pub mod answers;
+pub mod bench;
+pub mod bigint;
+pub mod check;
+pub mod count;
+pub mod day1;
+pub mod day2;
+pub mod day3;
+pub mod day4;
+pub mod day5;
+pub mod day6;
+pub mod day7;
+pub mod grid;
+pub mod input;
+pub mod int;
+pub mod interval;
+pub mod parse;
+pub mod solution;
+
+use solution::Solution;
+
+/// All solved days, to add a new day create the module and list it here.
+pub const SOLUTIONS: &[&dyn Solution] = &[
+	&day1::Day1,
+	&day2::Day2,
+	&day3::Day3,
+	&day4::Day4,
+	&day5::Day5,
+	&day6::Day6,
+	&day7::Day7,
+];
+
+pub fn solution(day: u32) -> Option<&'static dyn Solution> {
+	SOLUTIONS.iter().copied().find(|s| s.day() == day)
+}
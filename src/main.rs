@@ -0,0 +1,233 @@
+mod cli;
+
+use std::{env, fmt, fs, io, process::ExitCode};
+
+use adventofcode_2025::{
+	SOLUTIONS,
+	answers::{ANSWERS, Answers, Status, input_hash},
+	bench,
+	parse::ParseError,
+	solution,
+	solution::{Part, Solution, SolveError},
+};
+use cli::{Command, Selection};
+
+#[derive(Debug)]
+enum RunError {
+	Input(io::Error),
+	Parse(ParseError),
+	Solve(SolveError),
+}
+
+impl fmt::Display for RunError {
+	fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+		match self {
+			RunError::Input(e) => write!(f, "failed to read input: {e}"),
+			RunError::Parse(e) => write!(f, "invalid input: {e}"),
+			RunError::Solve(e) => write!(f, "{e}"),
+		}
+	}
+}
+
+fn run(solution: &dyn Solution, selection: &Selection) -> Result<(), RunError> {
+	println!("Day {}", solution.day());
+
+	let input = selection.input.load(solution).map_err(RunError::Input)?;
+
+	for part in selection.parts() {
+		let answer = solution.run(part, &input).map_err(RunError::Solve)?;
+		println!("  Part {part}: {answer}");
+	}
+
+	Ok(())
+}
+
+struct VerifyRow {
+	day: u32,
+	part: Part,
+	hash: u64,
+	answer: String,
+	expected: Option<String>,
+	status: Status,
+}
+
+fn verify(
+	solution: &dyn Solution,
+	selection: &Selection,
+	answers: &Answers,
+	rows: &mut Vec<VerifyRow>,
+) -> Result<(), RunError> {
+	let input = selection.input.load(solution).map_err(RunError::Input)?;
+	let hash = input_hash(&input);
+
+	for part in selection.parts() {
+		let answer = solution.run(part, &input).map_err(RunError::Solve)?;
+		let answer = answer.to_string();
+		let expected = answers.get(solution.day(), part, hash);
+
+		rows.push(VerifyRow {
+			day: solution.day(),
+			part,
+			hash,
+			status: Status::new(&answer, expected),
+			expected: expected.map(str::to_string),
+			answer,
+		});
+	}
+
+	Ok(())
+}
+
+fn print_verify_table(rows: &[VerifyRow]) {
+	let answer_w = rows.iter().map(|r| r.answer.len()).max().unwrap_or(0);
+	let answer_w = answer_w.max("answer".len());
+	let expected_w = rows
+		.iter()
+		.filter_map(|r| r.expected.as_ref().map(String::len))
+		.max()
+		.unwrap_or(0)
+		.max("expected".len());
+
+	println!(
+		"day  part  {:<16}  {:<answer_w$}  {:<expected_w$}  status",
+		"input hash", "answer", "expected"
+	);
+
+	for r in rows {
+		println!(
+			"{:>3}  {:>4}  {:016x}  {:<answer_w$}  {:<expected_w$}  {}",
+			r.day,
+			r.part,
+			r.hash,
+			r.answer,
+			r.expected.as_deref().unwrap_or("-"),
+			r.status
+		);
+	}
+
+	let count = |s| rows.iter().filter(|r| r.status == s).count();
+	println!(
+		"\n{} passed, {} failed, {} unknown",
+		count(Status::Pass),
+		count(Status::Fail),
+		count(Status::Unknown)
+	);
+}
+
+/// Looks up the solutions for all days, failing on the first unknown day.
+fn solutions(days: &[u32]) -> Result<Vec<&'static dyn Solution>, u32> {
+	days.iter().map(|&d| solution(d).ok_or(d)).collect()
+}
+
+fn main() -> ExitCode {
+	let max_day = SOLUTIONS.iter().map(|s| s.day()).max().unwrap_or(0);
+
+	let cmd = match cli::parse_args(env::args().skip(1), max_day) {
+		Ok(cmd) => cmd,
+		Err(e) => {
+			eprintln!("error: {e}\n\n{}", cli::USAGE);
+			return ExitCode::FAILURE;
+		}
+	};
+
+	let selection = match &cmd {
+		Command::Help => {
+			println!("{}", cli::USAGE);
+			return ExitCode::SUCCESS;
+		}
+		Command::Run(selection)
+		| Command::Verify { selection, .. }
+		| Command::Bench { selection, .. } => selection,
+	};
+
+	// make sure all days exist before we start running anything
+	let solutions = match solutions(&selection.days) {
+		Ok(s) => s,
+		Err(day) => {
+			eprintln!("error: day {day} is not solved yet");
+			return ExitCode::FAILURE;
+		}
+	};
+
+	match &cmd {
+		Command::Help => unreachable!(),
+		Command::Run(selection) => {
+			for solution in solutions {
+				if let Err(e) = run(solution, selection) {
+					eprintln!("error: day {}: {e}", solution.day());
+					return ExitCode::FAILURE;
+				}
+			}
+		}
+		Command::Verify { selection, answers } => {
+			let answers = match answers {
+				Some(path) => fs::read_to_string(path),
+				None => Ok(ANSWERS.to_string()),
+			};
+			let answers = match answers.as_deref().map(Answers::parse) {
+				Ok(Ok(answers)) => answers,
+				Ok(Err(e)) => {
+					eprintln!("error: invalid answers file: {e}");
+					return ExitCode::FAILURE;
+				}
+				Err(e) => {
+					eprintln!("error: failed to read answers file: {e}");
+					return ExitCode::FAILURE;
+				}
+			};
+
+			let mut rows = Vec::new();
+			let mut failed = false;
+
+			for solution in solutions {
+				if let Err(e) = verify(solution, selection, &answers, &mut rows)
+				{
+					eprintln!("error: day {}: {e}", solution.day());
+					failed = true;
+				}
+			}
+
+			print_verify_table(&rows);
+
+			if failed || rows.iter().any(|r| r.status == Status::Fail) {
+				return ExitCode::FAILURE;
+			}
+		}
+		Command::Bench {
+			selection,
+			runs,
+			json,
+		} => {
+			let parts = selection.parts();
+			let mut timings = Vec::new();
+
+			for solution in solutions {
+				let res = selection
+					.input
+					.load(solution)
+					.map_err(RunError::Input)
+					.and_then(|input| {
+						solution
+							.bench(&input, *runs, &parts)
+							.map_err(RunError::Parse)
+					});
+
+				match res {
+					Ok(t) => timings.push((solution.day(), t)),
+					Err(e) => {
+						eprintln!("error: day {}: {e}", solution.day());
+						return ExitCode::FAILURE;
+					}
+				}
+			}
+
+			if *json {
+				println!("{}", bench::json(*runs, &timings));
+			} else {
+				print!("{}", bench::table(&timings));
+			}
+		}
+	}
+
+	ExitCode::SUCCESS
+}
@@ -0,0 +1,101 @@
+use std::{error::Error, fmt};
+
+/// An error produced when a puzzle input could not be parsed.
+///
+/// Line and column are 1-based and point at the start of `snippet`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ParseError {
+	pub line: usize,
+	pub column: usize,
+	/// the offending part of the input, empty if something was missing
+	pub snippet: String,
+	/// description of what was expected instead
+	pub expected: String,
+}
+
+impl ParseError {
+	pub fn new(
+		line: usize,
+		column: usize,
+		snippet: impl Into<String>,
+		expected: impl Into<String>,
+	) -> Self {
+		Self {
+			line,
+			column,
+			snippet: snippet.into(),
+			expected: expected.into(),
+		}
+	}
+
+	/// Creates an error pointing at `snippet` which must be a subslice of
+	/// `input`, the line and column get calculated from its position.
+	///
+	/// ## Panics
+	/// If `snippet` does not point into `input`.
+	pub fn at(input: &str, snippet: &str, expected: impl Into<String>) -> Self {
+		let offset = (snippet.as_ptr() as usize)
+			.checked_sub(input.as_ptr() as usize)
+			.filter(|o| o + snippet.len() <= input.len())
+			.expect("snippet is not part of the input");
+
+		let before = &input[..offset];
+		let line_start = before.rfind('\n').map(|i| i + 1).unwrap_or(0);
+
+		Self::new(
+			before.matches('\n').count() + 1,
+			before[line_start..].chars().count() + 1,
+			snippet,
+			expected,
+		)
+	}
+
+	/// Creates an error pointing at the first char of `snippet`.
+	///
+	/// See [`ParseError::at`].
+	pub fn at_char(
+		input: &str,
+		snippet: &str,
+		expected: impl Into<String>,
+	) -> Self {
+		let len = snippet.chars().next().map(char::len_utf8).unwrap_or(0);
+		Self::at(input, &snippet[..len], expected)
+	}
+}
+
+impl fmt::Display for ParseError {
+	fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+		write!(
+			f,
+			"line {}, column {}: expected {}, ",
+			self.line, self.column, self.expected
+		)?;
+
+		if self.snippet.is_empty() {
+			f.write_str("found nothing")
+		} else {
+			write!(f, "found `{}`", self.snippet.escape_debug())
+		}
+	}
+}
+
+impl Error for ParseError {}
+
+#[test]
+fn test_at() {
+	let input = "L10\nR5\nX3\n";
+	let line = input.lines().nth(2).unwrap();
+
+	let err = ParseError::at_char(input, line, "`L` or `R`");
+	assert_eq!(err, ParseError::new(3, 1, "X", "`L` or `R`"));
+	assert_eq!(
+		err.to_string(),
+		"line 3, column 1: expected `L` or `R`, found `X`"
+	);
+
+	let err = ParseError::at(input, &line[1..], "a number");
+	assert_eq!((err.line, err.column), (3, 2));
+
+	let err = ParseError::at(input, &input[input.len()..], "more input");
+	assert_eq!((err.line, err.column, err.snippet.as_str()), (4, 1, ""));
+}
@@ -0,0 +1,99 @@
+use std::{error::Error, fmt};
+
+use crate::{bench::DayTimings, parse::ParseError};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum Part {
+	One,
+	Two,
+}
+
+impl Part {
+	pub const ALL: [Part; 2] = [Part::One, Part::Two];
+
+	pub fn new(num: u32) -> Option<Self> {
+		match num {
+			1 => Some(Part::One),
+			2 => Some(Part::Two),
+			_ => None,
+		}
+	}
+}
+
+impl fmt::Display for Part {
+	fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+		match self {
+			Part::One => f.pad("1"),
+			Part::Two => f.pad("2"),
+		}
+	}
+}
+
+/// Why a part could not be solved.
+#[derive(Debug)]
+pub enum SolveError {
+	Parse(ParseError),
+	/// The input is valid but has no answer, e.g. because it got too large.
+	Unsolvable(Box<dyn Error + Send + Sync>),
+}
+
+impl SolveError {
+	pub fn unsolvable(e: impl Error + Send + Sync + 'static) -> Self {
+		SolveError::Unsolvable(Box::new(e))
+	}
+}
+
+impl From<ParseError> for SolveError {
+	fn from(e: ParseError) -> Self {
+		SolveError::Parse(e)
+	}
+}
+
+impl fmt::Display for SolveError {
+	fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+		match self {
+			SolveError::Parse(e) => write!(f, "invalid input: {e}"),
+			SolveError::Unsolvable(e) => write!(f, "no answer: {e}"),
+		}
+	}
+}
+
+impl Error for SolveError {
+	fn source(&self) -> Option<&(dyn Error + 'static)> {
+		match self {
+			SolveError::Parse(e) => Some(e),
+			SolveError::Unsolvable(e) => Some(&**e),
+		}
+	}
+}
+
+/// A solved puzzle day which can be run by the `aoc` binary.
+///
+/// Every `dayN` module exposes a unit struct implementing this trait which
+/// then gets listed in [`SOLUTIONS`](crate::SOLUTIONS).
+pub trait Solution {
+	/// The day of december this puzzle belongs to.
+	fn day(&self) -> u32;
+
+	/// The puzzle input baked into the binary.
+	fn input(&self) -> &'static str;
+
+	fn part1(&self, input: &str) -> Result<u64, SolveError>;
+
+	fn part2(&self, input: &str) -> Result<u64, SolveError>;
+
+	/// Measures parsing and each of `parts` separately `runs` times.
+	fn bench(
+		&self,
+		input: &str,
+		runs: usize,
+		parts: &[Part],
+	) -> Result<DayTimings, ParseError>;
+
+	fn run(&self, part: Part, input: &str) -> Result<u64, SolveError> {
+		match part {
+			Part::One => self.part1(input),
+			Part::Two => self.part2(input),
+		}
+	}
+}
@@ -0,0 +1,52 @@
+use adventofcode_2025::{
+	SOLUTIONS,
+	answers::{ANSWERS, Answers, input_hash},
+	day1::{Dial, Dir},
+	day2::IdRange,
+	day3::BatteryBank,
+	day7,
+	solution::Part,
+};
+
+#[test]
+fn test_known_answers() {
+	let answers = Answers::parse(ANSWERS).unwrap();
+
+	for solution in SOLUTIONS {
+		let input = solution.input();
+		let hash = input_hash(input);
+
+		for part in Part::ALL {
+			let Some(expected) = answers.get(solution.day(), part, hash) else {
+				continue;
+			};
+
+			let answer = solution.run(part, input).unwrap();
+			assert_eq!(
+				answer.to_string(),
+				expected,
+				"day {} part {part}",
+				solution.day()
+			);
+		}
+	}
+}
+
+#[test]
+fn test_domain_types() {
+	let mut dial = Dial::new(50);
+	assert_eq!(dial.rotate_with_count(Dir::Left, 68), 1);
+	assert_eq!(dial.idx, 82);
+
+	assert_eq!(IdRange::new(95, 115).invalid_ids_p2().len(), 2);
+
+	assert_eq!(
+		BatteryBank::new("818181911112111").largest_jolt(2).value(),
+		Some(92)
+	);
+
+	let map = day7::parse_input(day7::INPUT).unwrap();
+	let timelines = map.timelines().unwrap();
+	assert_eq!(timelines.total(), 58097428661390);
+	assert!(timelines.path(timelines.total() - 1).is_some());
+}